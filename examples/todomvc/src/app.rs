@@ -3,8 +3,7 @@ use skima::tap;
 use skima::tree::Tree;
 use skima::web::prelude::*;
 use skima::web::WebSys;
-use wasm_bindgen::JsCast;
-use web_sys::{Event, KeyboardEvent};
+use web_sys::KeyboardEvent;
 
 use crate::action::TodoCreate;
 use crate::helpers::input_event_value;
@@ -18,8 +17,8 @@ pub fn app_header() -> impl Markup {
 		input((
 			classname("new-todo"),
 			attr("placeholder", "What needs to be done?"),
-			attr("autofocus", "true"),
-			tap(|tree| on("keydown", on_keydown(tree.clone()))),
+			bool_attr("autofocus", true),
+			tap(|tree| on_keydown(handle_keydown(tree.clone()))),
 		)),
 	))
 }
@@ -30,7 +29,7 @@ pub fn app_main() -> impl Markup {
 		input((
 			classname("toggle-all"),
 			attr("id", "toggle-all"),
-			attr("type", "checkbox"),
+			input_type(InputType::Checkbox),
 		)),
 		label((attr("for", "toggle-all"), "Mark all as complete")),
 		todo_list(),
@@ -54,9 +53,8 @@ pub fn app() -> impl Markup {
 	section((classname("todoapp"), app_header(), app_main()))
 }
 
-fn on_keydown(tree: Tree<WebSys>) -> impl Fn(Event) {
-	move |ev: Event| {
-		let ev: KeyboardEvent = ev.unchecked_into();
+fn handle_keydown(tree: Tree<WebSys>) -> impl Fn(KeyboardEvent) {
+	move |ev: KeyboardEvent| {
 		match ev.key().as_ref() {
 			"Enter" => tree.dispatch(Box::new(TodoCreate(input_event_value(&ev)))),
 			_ => {}
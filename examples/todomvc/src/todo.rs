@@ -9,7 +9,7 @@ use skima::web::WebSys;
 use skima::Markup;
 use uuid::Uuid;
 use wasm_bindgen::JsCast;
-use web_sys::{Event, HtmlInputElement, KeyboardEvent, Node};
+use web_sys::{HtmlInputElement, KeyboardEvent, Node};
 
 use crate::action::{TodoDelete, TodoEdit, TodoToggle};
 use crate::helpers::input_value;
@@ -31,7 +31,7 @@ pub fn todo_item(item: Var<TodoItem>) -> impl Markup {
 		let editing = cx.get::<Editing>().clone();
 		let input_ref = cx.mutable::<Option<Node>>(None);
 
-		let on_click = cx.callback_0({
+		let on_click = cx.callback({
 			let input_ref = input_ref.clone();
 			cx.wrap_0(move |cx| {
 				cx.update::<Editing>(|e| e.0 = !e.0);
@@ -44,11 +44,10 @@ pub fn todo_item(item: Var<TodoItem>) -> impl Markup {
 			})
 		});
 
-		let on_keydown = cx.callback_1_hash(&item, {
+		let handle_keydown = cx.callback_hash(&item, {
 			let item = item.clone();
 			let input_ref = input_ref.clone();
-			cx.wrap_1(move |cx, ev: Event| {
-				let ev = ev.unchecked_into::<KeyboardEvent>();
+			cx.wrap_1(move |cx, ev: KeyboardEvent| {
 				match ev.key().as_ref() {
 					"Enter" => {
 						update_title(&item, &input_ref, cx);
@@ -59,7 +58,7 @@ pub fn todo_item(item: Var<TodoItem>) -> impl Markup {
 			})
 		});
 
-		let on_blur = cx.callback_0_hash(
+		let on_blur = cx.callback_hash(
 			&item,
 			cx.wrap_0(enclose!((item, input_ref) move |cx| {
 				update_title(&item, &input_ref, cx);
@@ -67,14 +66,14 @@ pub fn todo_item(item: Var<TodoItem>) -> impl Markup {
 			})),
 		);
 
-		let on_destroy = cx.callback_0_hash(
+		let on_destroy = cx.callback_hash(
 			&item,
 			cx.wrap_0(enclose!((item) move |cx| {
 				destroy(&item, cx)
 			})),
 		);
 
-		let on_toggle = cx.callback_0_hash(
+		let on_toggle = cx.callback_hash(
 			&item,
 			cx.wrap_0(enclose!((item) move |cx| {
 				toggle(&item, cx)
@@ -90,20 +89,20 @@ pub fn todo_item(item: Var<TodoItem>) -> impl Markup {
 				Either::A(input((
 					reference(input_ref.setter()),
 					classname("edit"),
-					attr("value", item.title.to_owned()),
+					prop("value", item.title.to_owned()),
 					attr(
 						"onfocus",
 						"this.selectionStart = this.selectionEnd = this.value.length;",
 					),
-					(on("keydown", on_keydown), on("blur", on_blur)),
+					(on_keydown(handle_keydown), on("blur", on_blur)),
 				)))
 			} else {
 				Either::B(div((
 					classname("view"),
 					input((
 						classname("toggle"),
-						attr("type", "checkbox"),
-						item.is_done.then(|| attr("checked", "")),
+						input_type(InputType::Checkbox),
+						prop("checked", item.is_done),
 						on("click", on_toggle),
 					)),
 					label((item.title.to_owned(), on("click", on_click))),
@@ -1,7 +1,10 @@
 use proc_macro::TokenStream;
 use quote::quote;
+use syn::{parse_macro_input, DeriveInput};
 use syn_rsx::{parse, Node, NodeName};
 
+mod routable;
+
 /// Converts HTML to `String`.
 ///
 /// Values returned from braced blocks `{}` are expected to return something
@@ -26,6 +29,16 @@ pub fn html(tokens: TokenStream) -> TokenStream {
 	.into()
 }
 
+/// Implements `Routable` for an enum, every variant needs a `#[route("/path/:param")]`
+/// attribute. See `skima::web::router::routable` for the syntax of the patterns.
+#[proc_macro_derive(Routable, attributes(route))]
+pub fn derive_routable(tokens: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(tokens as DeriveInput);
+	routable::derive(input)
+		.unwrap_or_else(|error| error.to_compile_error())
+		.into()
+}
+
 fn format_nodes(nodes: Vec<Node>) -> proc_macro2::TokenStream {
 	if nodes.len() == 1 {
 		let node = nodes.into_iter().next().unwrap();
@@ -41,6 +54,17 @@ fn format_nodes(nodes: Vec<Node>) -> proc_macro2::TokenStream {
 fn format_node(node: Node) -> proc_macro2::TokenStream {
 	match node {
 		Node::Element(el) => {
+			// Void elements can't have children, check it at compile time
+			let check = match &el.name {
+				NodeName::Path(path) if !el.children.is_empty() => {
+					path.path.get_ident().map(|ident| {
+						let tag = ident.to_string();
+						quote!(const _: () = skima::web::tag::check_children(#tag);)
+					})
+				}
+				_ => None,
+			};
+
 			let path = match el.name {
 				NodeName::Path(path) => {
 					quote!(#path)
@@ -88,9 +112,17 @@ fn format_node(node: Node) -> proc_macro2::TokenStream {
 				nested.push(format_node(child))
 			}
 
-			quote! {#path((
+			let markup = quote! {#path((
 				#(#nested),*
-			))}
+			))};
+
+			match check {
+				Some(check) => quote!({
+					#check
+					#markup
+				}),
+				None => markup,
+			}
 		}
 		Node::Text(text) => {
 			let value = text.value.as_ref();
@@ -0,0 +1,275 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+	Data, DeriveInput, Error, Field, Fields, GenericArgument, Ident, LitStr, PathArguments, Type,
+	Variant,
+};
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream, Error> {
+	let Data::Enum(data) = &input.data else {
+		return Err(Error::new_spanned(
+			&input.ident,
+			"Routable can only be derived for enums",
+		));
+	};
+
+	let variants = data
+		.variants
+		.iter()
+		.map(RouteVariant::parse)
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let ident = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+	// Mixed site span, so that fields named `url` don't shadow it
+	let url = Ident::new("url", Span::mixed_site());
+	let to_url = variants.iter().map(|v| v.to_url(&url));
+	let from_url = variants.iter().map(|v| v.from_url(&url));
+
+	Ok(quote! {
+		impl #impl_generics skima::web::router::routable::Routable for #ident #ty_generics #where_clause {
+			fn to_url(&self) -> String {
+				let mut #url = skima::web::router::routable::UrlBuilder::default();
+				match self {
+					#(#to_url)*
+				}
+				#url.finish()
+			}
+
+			fn from_url(#url: &str) -> Option<Self> {
+				let #url = skima::web::router::routable::Url::parse(#url)?;
+				#(#from_url)*
+				None
+			}
+		}
+	})
+}
+
+enum Segment {
+	Literal(String),
+	Param(Ident),
+	Rest(Ident),
+}
+
+struct RouteVariant<'a> {
+	ident: &'a Ident,
+	segments: Vec<Segment>,
+	/// Fields that are not in the path, `Option` ones may be missing
+	query: Vec<(&'a Ident, Option<&'a Type>)>,
+	fields: Vec<&'a Field>,
+}
+
+impl<'a> RouteVariant<'a> {
+	fn parse(variant: &'a Variant) -> Result<Self, Error> {
+		let pattern = variant
+			.attrs
+			.iter()
+			.find(|attr| attr.path().is_ident("route"))
+			.ok_or_else(|| {
+				Error::new_spanned(&variant.ident, "expected a #[route(\"/path\")] attribute")
+			})?
+			.parse_args::<LitStr>()?;
+
+		let fields: Vec<&Field> = match &variant.fields {
+			Fields::Named(fields) => fields.named.iter().collect(),
+			Fields::Unit => Vec::new(),
+			Fields::Unnamed(_) => {
+				return Err(Error::new_spanned(
+					&variant.ident,
+					"routes with unnamed fields are not supported, name the fields after the parameters",
+				))
+			}
+		};
+
+		let field = |name: &str| {
+			fields
+				.iter()
+				.find(|f| f.ident.as_ref().map_or(false, |i| i == name))
+				.copied()
+				.ok_or_else(|| {
+					Error::new_spanned(
+						&pattern,
+						format!("`{}` is not a field of `{}`", name, variant.ident),
+					)
+				})
+		};
+
+		let value = pattern.value();
+		let parts: Vec<&str> = value.split('/').filter(|s| !s.is_empty()).collect();
+		let mut segments = Vec::new();
+
+		for (index, part) in parts.iter().enumerate() {
+			if let Some(name) = part.strip_prefix(':') {
+				let field = field(name)?;
+				if option_inner(&field.ty).is_some() {
+					return Err(Error::new_spanned(
+						&field.ty,
+						"path parameters can't be optional",
+					));
+				}
+				segments.push(Segment::Param(field.ident.clone().unwrap()));
+			} else if let Some(name) = part.strip_prefix('*') {
+				if index + 1 != parts.len() {
+					return Err(Error::new_spanned(
+						&pattern,
+						"`*` should be the last segment",
+					));
+				}
+				segments.push(Segment::Rest(field(name)?.ident.clone().unwrap()));
+			} else {
+				segments.push(Segment::Literal(part.to_string()));
+			}
+		}
+
+		let query = fields
+			.iter()
+			.filter(|f| {
+				!segments.iter().any(|s| match s {
+					Segment::Param(ident) | Segment::Rest(ident) => f.ident.as_ref() == Some(ident),
+					Segment::Literal(_) => false,
+				})
+			})
+			.map(|f| (f.ident.as_ref().unwrap(), option_inner(&f.ty)))
+			.collect();
+
+		Ok(RouteVariant {
+			ident: &variant.ident,
+			segments,
+			query,
+			fields,
+		})
+	}
+
+	fn field_idents(&self) -> impl Iterator<Item = &Ident> {
+		self.fields.iter().map(|f| f.ident.as_ref().unwrap())
+	}
+
+	fn field_type(&self, ident: &Ident) -> &Type {
+		&self
+			.fields
+			.iter()
+			.find(|f| f.ident.as_ref() == Some(ident))
+			.unwrap()
+			.ty
+	}
+
+	fn to_url(&self, url: &Ident) -> TokenStream {
+		let ident = self.ident;
+		let fields = self.field_idents();
+
+		let segments = self.segments.iter().map(|segment| match segment {
+			Segment::Literal(literal) => quote!(#url.segment(#literal);),
+			Segment::Param(param) => quote!(#url.param(#param);),
+			Segment::Rest(rest) => quote!(#url.rest(#rest);),
+		});
+
+		let query = self.query.iter().map(|(field, option)| {
+			let name = field.to_string();
+			match option {
+				Some(_) => quote! {
+					if let Some(value) = #field {
+						#url.query(#name, value);
+					}
+				},
+				None => quote!(#url.query(#name, #field);),
+			}
+		});
+
+		quote! {
+			Self::#ident { #(#fields),* } => {
+				#(#segments)*
+				#(#query)*
+			}
+		}
+	}
+
+	fn from_url(&self, url: &Ident) -> TokenStream {
+		let ident = self.ident;
+		let fields = self.field_idents();
+
+		let has_rest = matches!(self.segments.last(), Some(Segment::Rest(_)));
+		let fixed = self.segments.len() - has_rest as usize;
+		let length = if has_rest {
+			quote!(#url.segments.len() < #fixed)
+		} else {
+			quote!(#url.segments.len() != #fixed)
+		};
+
+		let segments = self
+			.segments
+			.iter()
+			.enumerate()
+			.map(|(index, segment)| match segment {
+				Segment::Literal(literal) => quote! {
+					if #url.segments[#index] != #literal {
+						return None;
+					}
+				},
+				Segment::Param(param) => {
+					let ty = self.field_type(param);
+					quote! {
+						let #param = #url.segments[#index].parse::<#ty>().ok()?;
+					}
+				}
+				Segment::Rest(rest) => {
+					let ty = self.field_type(rest);
+					quote! {
+						let #rest = #url.segments[#index..].join("/").parse::<#ty>().ok()?;
+					}
+				}
+			});
+
+		let query = self.query.iter().map(|(field, option)| {
+			let name = field.to_string();
+			match option {
+				Some(ty) => quote! {
+					let #field = match #url.query(#name) {
+						Some(value) => Some(value.parse::<#ty>().ok()?),
+						None => None,
+					};
+				},
+				None => {
+					let ty = self.field_type(field);
+					quote! {
+						let #field = #url.query(#name)?.parse::<#ty>().ok()?;
+					}
+				}
+			}
+		});
+
+		quote! {
+			let matcher = || -> Option<Self> {
+				if #length {
+					return None;
+				}
+				#(#segments)*
+				#(#query)*
+				Some(Self::#ident { #(#fields),* })
+			};
+
+			if let Some(route) = matcher() {
+				return Some(route);
+			}
+		}
+	}
+}
+
+/// `T` of `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+	let Type::Path(path) = ty else {
+		return None;
+	};
+
+	let segment = path.path.segments.last()?;
+	if segment.ident != "Option" {
+		return None;
+	}
+
+	match &segment.arguments {
+		PathArguments::AngleBracketed(args) => match args.args.first()? {
+			GenericArgument::Type(ty) => Some(ty),
+			_ => None,
+		},
+		_ => None,
+	}
+}
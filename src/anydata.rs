@@ -88,6 +88,11 @@ impl AnyData {
 		T::from_dyn(data)
 	}
 
+	#[inline]
+	pub fn retain(&mut self, mut func: impl FnMut(u64) -> bool) {
+		self.data.retain(|key, _| func(*key));
+	}
+
 	#[inline]
 	pub fn set_with_key<T: Envelope>(&mut self, key: u64, value: T) {
 		self.data.insert(key, T::to_dyn(value));
@@ -0,0 +1,57 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::tree::Tree;
+use crate::{render_subtree, Backend, Markup};
+
+/// A difference between the markup and the DOM it was hydrated against.
+/// Every mismatch is repaired by re-creating the node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HydrationMismatch {
+	Element {
+		expected: &'static str,
+		found: Option<String>,
+	},
+	Text {
+		expected: String,
+		found: Option<String>,
+	},
+	/// A node not described by the markup, removed after hydration
+	Extra { found: String },
+	/// An attribute not set by the markup, removed after hydration
+	Attribute { name: String },
+}
+
+#[derive(Default)]
+pub struct Hydration {
+	active: Cell<bool>,
+	mismatches: RefCell<Vec<HydrationMismatch>>,
+}
+
+impl Hydration {
+	pub(crate) fn is_active(&self) -> bool {
+		self.active.get()
+	}
+
+	pub(crate) fn report(&self, mismatch: HydrationMismatch) {
+		tracing::warn!("Hydration mismatch: {:?}", mismatch);
+		self.mismatches.borrow_mut().push(mismatch);
+	}
+}
+
+/// Renders the markup into `root`, reusing the nodes that are already
+/// present in the root element instead of creating new ones.
+pub fn hydrate<M: Markup<B>, B: Backend>(markup: &mut M, root: &Tree<B>) -> Vec<HydrationMismatch> {
+	let hydration = Rc::new(Hydration::default());
+	hydration.active.set(true);
+
+	root.hydration.replace(Some(hydration.clone()));
+	render_subtree(markup, root);
+	root.remove_unclaimed();
+
+	// Trees keep the reference, but nothing is claimed after this point
+	hydration.active.set(false);
+	root.hydration.replace(None);
+
+	hydration.mismatches.take()
+}
@@ -10,6 +10,9 @@
 #![feature(error_generic_member_access)]
 #![feature(error_in_core)]
 
+// Lets generated code refer to `skima::` inside of the crate itself
+extern crate self as skima;
+
 use std::any::Any;
 use std::marker::PhantomData;
 
@@ -26,6 +29,7 @@ mod debug;
 pub mod dynamic;
 pub mod effect;
 pub mod ext;
+pub mod hydrate;
 mod iter;
 pub mod list;
 pub mod reference;
@@ -151,6 +155,23 @@ pub fn subtree<M: Markup<B>, B: Backend>(parent: &Tree<B>) -> Tree<B> {
 	}
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Namespace {
+	Html,
+	Svg,
+	MathMl,
+}
+
+impl Namespace {
+	pub fn uri(&self) -> &'static str {
+		match self {
+			Namespace::Html => "http://www.w3.org/1999/xhtml",
+			Namespace::Svg => "http://www.w3.org/2000/svg",
+			Namespace::MathMl => "http://www.w3.org/1998/Math/MathML",
+		}
+	}
+}
+
 pub trait Backend: std::fmt::Debug + Clone {
 	type Element: std::fmt::Debug + Clone;
 	type Text: std::fmt::Debug + Clone;
@@ -163,10 +184,22 @@ pub trait Backend: std::fmt::Debug + Clone {
 	fn insert(cursor: Self::Cursor, node: &Self::Node);
 	fn remove(node: &Self::Node);
 
+	/// Moves an already attached node to the cursor position
+	fn move_node(cursor: Self::Cursor, node: &Self::Node) {
+		Self::remove(node);
+		Self::insert(cursor, node);
+	}
+
 	fn cursor_beginning_of(node: &Self::Element) -> Self::Cursor;
 	fn cursor_after(node: &Self::Node) -> Self::Cursor;
 
 	fn create_element(&self, tag: &'static str) -> Self::Element;
+
+	/// Backends that don't distinguish namespaces can keep the default
+	fn create_element_ns(&self, namespace: Namespace, tag: &'static str) -> Self::Element {
+		let _ = namespace;
+		self.create_element(tag)
+	}
 	fn create_text(&self, data: &str) -> Self::Text;
 
 	fn set_text(&self, text: &Self::Text, data: &str);
@@ -178,6 +211,32 @@ pub trait Backend: std::fmt::Debug + Clone {
 	fn node_to_text(node: Self::Node) -> Option<Self::Text>;
 
 	fn print_node(node: &Self::Node);
+
+	// Hydration support. Backends that cannot hydrate keep the defaults
+	// and every node is reported as a mismatch.
+
+	fn first_child(_element: &Self::Element) -> Option<Self::Node> {
+		None
+	}
+
+	fn next_sibling(_node: &Self::Node) -> Option<Self::Node> {
+		None
+	}
+
+	/// Returns the element if `node` is an element with the given namespace and tag
+	fn claim_element(
+		_node: &Self::Node,
+		_namespace: Namespace,
+		_tag: &str,
+	) -> Option<Self::Element> {
+		None
+	}
+
+	/// Returns the text if `node` is a text node with the given data.
+	/// Text nodes merged by the HTML parser may be split to match.
+	fn claim_text(_node: &Self::Node, _data: &str) -> Option<Self::Text> {
+		None
+	}
 }
 
 impl<B: Backend> Markup<B> for () {
@@ -1,5 +1,5 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
@@ -69,7 +69,6 @@ where
 		}
 	}
 
-	// TODO: do we need to implement key-based moves?
 	fn diff(&mut self, prev: &mut Self, tree: &crate::tree::Tree<B>) {
 		let mut prev_state = prev.state.borrow_mut();
 		let mut next_state = self.state.borrow_mut();
@@ -113,7 +112,7 @@ where
 			let prev_item = prev_iter.next_back().unwrap();
 			let next_item = next_iter.next_back().unwrap();
 
-			let tree = tree.child_at(prev_range.start);
+			let tree = tree.child_at(prev_range.end - 1);
 
 			let prev_m = prev_markup.get_mut(prev_item.0).unwrap();
 			let mut next_m = (self.func)(next_item.1, next_item.0);
@@ -154,45 +153,64 @@ where
 			return;
 		}
 
-		for prev_item in prev_iter {
-			match next_iter.next() {
-				None => {
-					let subtree = tree.child_at(prev_range.start);
-
-					let prev_m = prev_markup.get_mut(prev_item.0).unwrap();
-
-					prev_m.drop(&subtree, true);
-					subtree.disconnect(true);
+		// Match the rest by key
+		let prev_keys = prev_iter.map(|item| item.0).collect::<Vec<_>>();
+		let next_items = next_iter.collect::<Vec<_>>();
+
+		let next_positions = next_items
+			.iter()
+			.enumerate()
+			.map(|(i, item)| (item.0, i))
+			.collect::<HashMap<_, _>>();
+
+		let prev_trees = prev_keys
+			.iter()
+			.enumerate()
+			.map(|(i, key)| (*key, tree.child_at(prev_range.start + i)))
+			.collect::<HashMap<_, _>>();
+
+		// Remove from the end to keep indices stable
+		for (i, key) in prev_keys.iter().enumerate().rev() {
+			if !next_positions.contains_key(key) {
+				let index = prev_range.start + i;
+				let subtree = tree.child_at(index);
+				prev_markup.get_mut(*key).unwrap().drop(&subtree, true);
+				tree.remove_at(index);
+			}
+		}
 
-					// TODO: remove from the end instead, this version is super inefficient
-					tree.remove_at(prev_range.start);
-				}
-				Some(next_item) => {
-					let tree = tree.child_at(prev_range.start);
+		// Items that keep their relative order don't need to move
+		let sources = prev_keys
+			.iter()
+			.filter_map(|key| next_positions.get(key).copied())
+			.collect::<Vec<_>>();
 
-					let prev_m = prev_markup.get_mut(prev_item.0).unwrap();
-					let mut next_m = (self.func)(next_item.1, next_item.0);
+		let stable = longest_increasing_subsequence(&sources)
+			.into_iter()
+			.map(|i| sources[i])
+			.collect::<HashSet<_>>();
 
-					next_m.diff(prev_m, &tree);
+		for (i, next_item) in next_items.into_iter().enumerate() {
+			let index = next_range.start + i;
+			let mut next_m = (self.func)(next_item.1, next_item.0);
 
-					next_markup.insert(next_item.0.clone(), next_m);
+			match prev_trees.get(next_item.0) {
+				Some(subtree) => {
+					tree.move_child(tree.index_of(subtree).unwrap(), index);
+					if !stable.contains(&i) {
+						subtree.relocate();
+					}
 
-					prev_range.start += 1;
-					next_range.start += 1;
+					let prev_m = prev_markup.get_mut(next_item.0).unwrap();
+					next_m.diff(prev_m, subtree);
+				}
+				None => {
+					let subtree = tree.insert_at(index);
+					next_m.render(&subtree);
 				}
 			}
-		}
-
-		for next_item in next_iter {
-			let subtree = tree.insert_at(next_range.start);
-			let mut markup = (self.func)(next_item.1, next_item.0);
-			markup.render(&subtree);
-
-			next_markup.insert(next_item.0.clone(), markup);
 
-			next_range.start += 1;
-			next_range.start += 1;
-			// INSERTS
+			next_markup.insert(next_item.0.clone(), next_m);
 		}
 	}
 
@@ -215,3 +233,47 @@ where
 		tree.clear()
 	}
 }
+
+/// Indices of a longest strictly increasing subsequence of `items`
+fn longest_increasing_subsequence(items: &[usize]) -> Vec<usize> {
+	// `tails[k]` is the index of the smallest tail of a subsequence of length `k + 1`
+	let mut tails: Vec<usize> = Vec::new();
+	let mut prev = vec![None; items.len()];
+
+	for (i, item) in items.iter().enumerate() {
+		let k = tails.partition_point(|t| items[*t] < *item);
+		prev[i] = k.checked_sub(1).map(|k| tails[k]);
+		if k == tails.len() {
+			tails.push(i);
+		} else {
+			tails[k] = i;
+		}
+	}
+
+	let mut result = Vec::with_capacity(tails.len());
+	let mut cursor = tails.last().copied();
+	while let Some(i) = cursor {
+		result.push(i);
+		cursor = prev[i];
+	}
+
+	result.reverse();
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_lis() {
+		assert_eq!(longest_increasing_subsequence(&[]), Vec::<usize>::new());
+		assert_eq!(longest_increasing_subsequence(&[0, 1, 2]), vec![0, 1, 2]);
+		assert_eq!(longest_increasing_subsequence(&[2, 1, 0]), vec![2]);
+		assert_eq!(longest_increasing_subsequence(&[2, 0, 3, 1]), vec![1, 3]);
+		assert_eq!(
+			longest_increasing_subsequence(&[4, 0, 5, 1, 2, 6, 3]),
+			vec![1, 3, 4, 6]
+		);
+	}
+}
@@ -10,8 +10,9 @@ use indexmap::IndexSet;
 
 use crate::action::Action;
 use crate::anydata::AnyData;
+use crate::hydrate::{Hydration, HydrationMismatch};
 use crate::web::dispatch::{ActionHandler, ActionResult};
-use crate::Backend;
+use crate::{Backend, Namespace};
 
 pub struct Tree<B: Backend>(Rc<TreeInner<B>>);
 
@@ -58,6 +59,9 @@ pub struct TreeInner<B: Backend> {
 	// Mutable element state
 	pub(crate) data: RefCell<AnyData>,
 
+	// Set while the tree is being hydrated
+	pub(crate) hydration: RefCell<Option<Rc<Hydration>>>,
+
 	pub(crate) backend: B,
 }
 
@@ -109,6 +113,7 @@ impl<B: Backend> Tree<B> {
 			children: RefCell::new(IndexSet::new()),
 			node: RefCell::new(Some(B::element_to_node(node))),
 			data: RefCell::new(Default::default()),
+			hydration: RefCell::new(None),
 			backend: data,
 			#[cfg(debug_assertions)]
 			name: RefCell::new(Cow::Borrowed("")),
@@ -125,6 +130,7 @@ impl<B: Backend> Tree<B> {
 			children: RefCell::new(IndexSet::new()),
 			node: RefCell::new(None),
 			data: RefCell::new(Default::default()),
+			hydration: RefCell::new(None),
 			backend: backend,
 			#[cfg(debug_assertions)]
 			name: RefCell::new(Cow::Borrowed("")),
@@ -141,6 +147,7 @@ impl<B: Backend> Tree<B> {
 			children: RefCell::new(IndexSet::new()),
 			node: RefCell::new(None),
 			data: RefCell::new(Default::default()),
+			hydration: RefCell::new(parent.hydration.borrow().clone()),
 			backend: parent.backend.clone(),
 			#[cfg(debug_assertions)]
 			name: RefCell::new(Cow::Borrowed("")),
@@ -169,6 +176,7 @@ impl<B: Backend> Tree<B> {
 			children: RefCell::new(IndexSet::new()),
 			node: RefCell::new(None),
 			data: RefCell::new(Default::default()),
+			hydration: RefCell::new(self.hydration.borrow().clone()),
 			backend: self.backend.clone(),
 			#[cfg(debug_assertions)]
 			name: RefCell::new(Cow::Borrowed("")),
@@ -210,6 +218,79 @@ impl<B: Backend> Tree<B> {
 		element.disconnect(true);
 	}
 
+	/// Moves the child at `from` to `to`, the DOM is left untouched
+	pub fn move_child(&self, from: usize, to: usize) {
+		if from == to {
+			return;
+		}
+
+		let child = self.child_at(from);
+		match (child.prev.take(), child.next.take()) {
+			(Some(prev), Some(next)) => {
+				prev.next.replace(Some(next.clone()));
+				next.prev.replace(Some(prev));
+			}
+			(Some(prev), None) => {
+				prev.next.replace(None);
+			}
+			(None, Some(next)) => {
+				next.prev.replace(None);
+			}
+			(None, None) => {}
+		}
+
+		self.children.borrow_mut().move_index(from, to);
+
+		let children = self.children.borrow();
+		let prev = to
+			.checked_sub(1)
+			.and_then(|i| children.get_index(i))
+			.map(|c| c.0.clone());
+		let next = children.get_index(to + 1).map(|c| c.0.clone());
+
+		if let Some(prev) = &prev {
+			prev.next.replace(Some(child.clone()));
+		}
+
+		if let Some(next) = &next {
+			next.prev.replace(Some(child.clone()));
+		}
+
+		child.prev.replace(prev);
+		child.next.replace(next);
+	}
+
+	pub fn index_of(&self, child: &Tree<B>) -> Option<usize> {
+		self.children
+			.borrow()
+			.get_index_of(&ByAddress(child.clone()))
+	}
+
+	/// Moves the DOM nodes of this tree to match its position among the siblings
+	pub fn relocate(&self) {
+		let mut nodes = Vec::new();
+		self.top_nodes(&mut nodes);
+
+		let mut cursor = self.find_pacement();
+		for node in nodes {
+			if let Some(cursor) = cursor.take() {
+				B::move_node(cursor, &node);
+			}
+			cursor = Some(B::cursor_after(&node));
+		}
+	}
+
+	fn top_nodes(&self, nodes: &mut Vec<B::Node>) {
+		if let Some(node) = self.node.borrow().as_ref() {
+			nodes.push(node.clone());
+			return;
+		}
+
+		for child in self.children.borrow().iter() {
+			child.top_nodes(nodes)
+		}
+	}
+
 	pub fn first_child(&self) -> Tree<B> {
 		self.children
 			.borrow()
@@ -336,6 +417,11 @@ impl<B: Backend> Tree<B> {
 	}
 
 	pub fn attach(&self, prev: Option<B::Node>) {
+		if self.hydration().is_some() {
+			// Claimed nodes are already in place
+			return;
+		}
+
 		let node = self.node.borrow();
 		let node = node.as_ref().unwrap();
 		if let Some(prev) = prev {
@@ -345,6 +431,128 @@ impl<B: Backend> Tree<B> {
 		}
 	}
 
+	/// Hydration in progress, `None` once the tree created its own node
+	pub(crate) fn hydration(&self) -> Option<Rc<Hydration>> {
+		self.hydration
+			.borrow()
+			.as_ref()
+			.filter(|h| h.is_active())
+			.cloned()
+	}
+
+	/// Creates an element, or claims the existing one when hydrating
+	pub fn create_element(&self, tag: &'static str) -> B::Element {
+		self.create_element_ns(Namespace::Html, tag)
+	}
+
+	pub fn create_element_ns(&self, namespace: Namespace, tag: &'static str) -> B::Element {
+		let Some(hydration) = self.hydration() else {
+			return self.backend.create_element_ns(namespace, tag);
+		};
+
+		let candidate = self.hydration_candidate();
+		if let Some(element) = candidate
+			.as_ref()
+			.and_then(|c| B::claim_element(c, namespace, tag))
+		{
+			return element;
+		}
+
+		hydration.report(HydrationMismatch::Element {
+			expected: tag,
+			found: candidate.as_ref().map(|c| format!("{:?}", c)),
+		});
+
+		self.stop_hydration(candidate);
+		self.backend.create_element_ns(namespace, tag)
+	}
+
+	/// Creates a text node, or claims the existing one when hydrating
+	pub fn create_text(&self, data: &str) -> B::Text {
+		let Some(hydration) = self.hydration() else {
+			return self.backend.create_text(data);
+		};
+
+		let candidate = self.hydration_candidate();
+		if let Some(text) = candidate.as_ref().and_then(|c| B::claim_text(c, data)) {
+			return text;
+		}
+
+		if data.is_empty() {
+			// Empty text is never serialized, so there is nothing to claim
+			self.stop_hydration(None);
+		} else {
+			hydration.report(HydrationMismatch::Text {
+				expected: data.to_owned(),
+				found: candidate.as_ref().map(|c| format!("{:?}", c)),
+			});
+			self.stop_hydration(candidate);
+		}
+
+		self.backend.create_text(data)
+	}
+
+	// The node is re-created and attached as usual, together with the
+	// whole subtree. The mismatched node is removed to free its place.
+	fn stop_hydration(&self, candidate: Option<B::Node>) {
+		if let Some(candidate) = candidate {
+			B::remove(&candidate);
+		}
+
+		self.hydration.replace(None);
+	}
+
+	/// The existing node this tree is expected to claim, found the
+	/// same way as `find_pacement` finds the insertion point.
+	fn hydration_candidate(&self) -> Option<B::Node> {
+		let mut cursor = self.clone();
+
+		loop {
+			while cursor.prev.borrow().is_none() {
+				let parent = cursor.parent.clone()?;
+				let node = parent.node.borrow().clone();
+				if let Some(node) = node {
+					return B::first_child(&B::node_to_element(node).unwrap());
+				}
+				cursor = parent;
+			}
+
+			let prev = cursor.prev();
+			cursor = prev;
+
+			if let Some(node) = cursor.last_node() {
+				return B::next_sibling(&node);
+			}
+		}
+	}
+
+	/// Removes the DOM children that were left unclaimed after hydration
+	pub(crate) fn remove_unclaimed(&self) {
+		let Some(hydration) = self.hydration() else {
+			return;
+		};
+
+		let last = self
+			.children
+			.borrow()
+			.iter()
+			.rev()
+			.find_map(|child| child.last_node());
+
+		let mut next = match last {
+			Some(node) => B::next_sibling(&node),
+			None => B::first_child(&B::node_to_element(self.node().clone()).unwrap()),
+		};
+
+		while let Some(node) = next {
+			next = B::next_sibling(&node);
+			hydration.report(HydrationMismatch::Extra {
+				found: format!("{:?}", node),
+			});
+			B::remove(&node);
+		}
+	}
+
 	pub fn fist_node(&self) -> Option<B::Node> {
 		if let Some(node) = self.node.borrow().as_ref() {
 			return Some(node.clone());
@@ -1,14 +1,35 @@
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+use std::rc::Rc;
+
+use indexmap::{IndexMap, IndexSet};
 
 use super::HtmlBackend;
+use crate::hydrate::{Hydration, HydrationMismatch};
 use crate::tree::Tree;
-use crate::Markup;
+use crate::{Backend, Markup};
 
 #[derive(Clone)]
 pub struct ClassList<S: StringLike>(S);
 
+/// Set of classes, see [`classes`]
+#[derive(Clone)]
+pub struct Classes<S: StringLike>(Vec<(S, bool)>);
+
 #[derive(Clone)]
 pub struct Attr<S1: StringLike, S2: StringLike>(S1, S2);
+
+/// Attributes known at runtime, see [`attrs`]
+#[derive(Clone)]
+pub struct Attrs<S1: AttrName, S2: StringLike>(IndexMap<S1, S2>);
+
+/// Attribute name that can be used as a key of [`Attrs`]
+pub trait AttrName: StringLike + Hash + Eq + Borrow<str> {}
+
+impl<S: StringLike + Hash + Eq + Borrow<str>> AttrName for S {}
 pub trait StringLike: AsRef<str> {
 	const DYNAMIC: bool;
 }
@@ -25,18 +46,183 @@ impl<'a> StringLike for Cow<'a, str> {
 	const DYNAMIC: bool = true;
 }
 
-pub fn classname<S: StringLike>(classname: S) -> Attr<&'static str, S> {
-	attr("class", classname)
+/// Adds the space-separated classes, the same as [`classlist`].
+/// Use `attr("class", ..)` to replace the whole attribute instead.
+pub fn classname<S: StringLike>(classname: S) -> ClassList<S> {
+	ClassList(classname)
 }
 
+/// Adds the space-separated classes, only the changed tokens are updated
 pub const fn classlist<S: StringLike>(classname: S) -> ClassList<S> {
 	ClassList(classname)
 }
 
+/// Spreads a set of attributes, e.g. `data-*` attributes passed down from the parent.
+/// Accepts an [`IndexMap`] or any iterator of pairs, later pairs win.
+pub fn attrs<S1, S2, I>(attrs: I) -> Attrs<S1, S2>
+where
+	S1: AttrName,
+	S2: StringLike,
+	I: IntoIterator<Item = (S1, S2)>,
+{
+	Attrs(attrs.into_iter().collect())
+}
+
+/// Adds the classes that are switched on, e.g.
+/// `classes([("todo", true), ("completed", item.is_done)])`.
+///
+/// Can be combined with [`classlist`] on the same element, a class added by both
+/// stays until both remove it. Only the changed tokens are updated.
+pub fn classes<S, I>(classes: I) -> Classes<S>
+where
+	S: StringLike,
+	I: IntoIterator,
+	I::Item: IntoClass<S>,
+{
+	Classes(classes.into_iter().map(IntoClass::into_class).collect())
+}
+
+/// Item of [`classes`]: a class or a `(class, enabled)` pair
+pub trait IntoClass<S> {
+	fn into_class(self) -> (S, bool);
+}
+
+impl<S: StringLike> IntoClass<S> for S {
+	fn into_class(self) -> (S, bool) {
+		(self, true)
+	}
+}
+
+impl<S: StringLike> IntoClass<S> for (S, bool) {
+	fn into_class(self) -> (S, bool) {
+		self
+	}
+}
+
+impl<S: StringLike + Default> IntoClass<S> for Option<S> {
+	fn into_class(self) -> (S, bool) {
+		match self {
+			Some(class) => (class, true),
+			None => (S::default(), false),
+		}
+	}
+}
+
 pub fn attr<S1: StringLike, S2: StringLike>(attr: S1, value: S2) -> Attr<S1, S2> {
 	Attr(attr, value)
 }
 
+fn tokens(classes: &str) -> IndexSet<&str> {
+	classes.split_ascii_whitespace().collect()
+}
+
+/// Number of class markups that added each class to the element
+#[derive(Default)]
+struct ClassTokens(RefCell<HashMap<String, usize>>);
+
+/// Tree of the closest node, attribute markup doesn't have its own node
+fn element_tree<B: Backend>(tree: &Tree<B>) -> Tree<B> {
+	let mut tree = tree.clone();
+	while tree.node.borrow().is_none() {
+		tree = tree.parent.clone().unwrap();
+	}
+	tree
+}
+
+fn class_tokens<B: Backend>(tree: &Tree<B>) -> Rc<ClassTokens> {
+	let tree = element_tree(tree);
+	let existing = tree.data().try_get::<Rc<ClassTokens>>();
+	existing.unwrap_or_else(|| {
+		let tokens = Rc::new(ClassTokens::default());
+		tree.data_mut().set(tokens.clone());
+		tokens
+	})
+}
+
+/// Attributes set by the markup of an element claimed during hydration
+#[derive(Default)]
+pub(crate) struct ClaimedAttributes(RefCell<HashSet<String>>);
+
+/// Starts tracking the attributes set by the markup of the element of `tree`
+pub(crate) fn start_claim<B: Backend>(tree: &Tree<B>) {
+	tree.data_mut().set(Rc::new(ClaimedAttributes::default()));
+}
+
+/// Keeps the server attribute when the element is hydrated
+pub(crate) fn claim_attribute<B: Backend>(tree: &Tree<B>, name: &str) {
+	if tree.hydration().is_none() {
+		return;
+	}
+
+	let tree = element_tree(tree);
+	let claimed = tree.data().try_get::<Rc<ClaimedAttributes>>();
+	if let Some(claimed) = claimed {
+		claimed.0.borrow_mut().insert(name.to_owned());
+	}
+}
+
+/// Removes the server attributes that the markup didn't set
+pub(crate) fn remove_unclaimed_attributes<B: HtmlBackend>(tree: &Tree<B>, hydration: &Hydration) {
+	let claimed = tree.data_mut().remove::<Rc<ClaimedAttributes>>();
+	let claimed = claimed.0.borrow();
+	let element = B::node_to_element(tree.node().clone()).unwrap();
+
+	for name in tree.backend.attribute_names(&element) {
+		if !claimed.contains(&name) {
+			tree.backend.remove_attribute(&element, &name);
+			hydration.report(HydrationMismatch::Attribute { name });
+		}
+	}
+}
+
+pub(crate) fn add_classes<'a, B: HtmlBackend>(
+	tree: &Tree<B>,
+	classes: impl IntoIterator<Item = &'a str>,
+) {
+	claim_attribute(tree, "class");
+
+	let element = B::node_to_element(tree.closest_node()).unwrap();
+	let tokens = class_tokens(tree);
+	let mut counts = tokens.0.borrow_mut();
+
+	for class in classes {
+		let count = counts.entry(class.to_owned()).or_default();
+		*count += 1;
+		if *count == 1 {
+			tree.backend.add_class(&element, class);
+		}
+	}
+}
+
+pub(crate) fn remove_classes<'a, B: HtmlBackend>(
+	tree: &Tree<B>,
+	classes: impl IntoIterator<Item = &'a str>,
+) {
+	let element = B::node_to_element(tree.closest_node()).unwrap();
+	let tokens = class_tokens(tree);
+	let mut counts = tokens.0.borrow_mut();
+
+	for class in classes {
+		match counts.get_mut(class) {
+			Some(count) if *count > 1 => *count -= 1,
+			_ => {
+				counts.remove(class);
+				tree.backend.remove_class(&element, class);
+			}
+		}
+	}
+}
+
+/// Removes the tokens that are gone and adds the new ones
+fn diff_tokens<B: HtmlBackend>(tree: &Tree<B>, prev: &IndexSet<&str>, next: &IndexSet<&str>) {
+	if prev == next {
+		return;
+	}
+
+	remove_classes(tree, prev.iter().copied().filter(|c| !next.contains(c)));
+	add_classes(tree, next.iter().copied().filter(|c| !prev.contains(c)));
+}
+
 impl<B: HtmlBackend, S: StringLike> Markup<B> for ClassList<S> {
 	fn has_own_node() -> bool {
 		false
@@ -47,22 +233,53 @@ impl<B: HtmlBackend, S: StringLike> Markup<B> for ClassList<S> {
 	}
 
 	fn render(&mut self, tree: &Tree<B>) {
-		let element = B::node_to_element(tree.closest_node()).unwrap();
-		tree.backend.add_class(&element, self.0.as_ref())
+		add_classes(tree, tokens(self.0.as_ref()))
 	}
 
 	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
 		if prev.0.as_ref() != self.0.as_ref() {
-			let element = B::node_to_element(tree.closest_node()).unwrap();
-			tree.backend.remove_class(&element, prev.0.as_ref());
-			tree.backend.add_class(&element, self.0.as_ref());
+			diff_tokens(tree, &tokens(prev.0.as_ref()), &tokens(self.0.as_ref()));
 		}
 	}
 
 	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
 		if should_unmount {
-			let element = B::node_to_element(tree.closest_node()).unwrap();
-			tree.backend.remove_class(&element, self.0.as_ref());
+			remove_classes(tree, tokens(self.0.as_ref()))
+		}
+	}
+}
+
+impl<S: StringLike> Classes<S> {
+	fn tokens(&self) -> IndexSet<&str> {
+		self.0
+			.iter()
+			.filter(|(_, enabled)| *enabled)
+			.flat_map(|(class, _)| class.as_ref().split_ascii_whitespace())
+			.collect()
+	}
+}
+
+impl<B: HtmlBackend, S: StringLike> Markup<B> for Classes<S> {
+	fn has_own_node() -> bool {
+		false
+	}
+
+	fn dynamic() -> bool {
+		// The flags can change even if the classes are static
+		true
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		add_classes(tree, self.tokens())
+	}
+
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		diff_tokens(tree, &prev.tokens(), &self.tokens());
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		if should_unmount {
+			remove_classes(tree, self.tokens())
 		}
 	}
 }
@@ -77,13 +294,19 @@ impl<B: HtmlBackend, S1: StringLike, S2: StringLike> Markup<B> for Attr<S1, S2>
 	}
 
 	fn render(&mut self, tree: &Tree<B>) {
+		claim_attribute(tree, self.0.as_ref());
 		let element = B::node_to_element(tree.closest_node()).unwrap();
 		tree.backend
 			.set_attribute(&element, self.0.as_ref(), self.1.as_ref())
 	}
 
 	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
-		if prev.1.as_ref() != self.1.as_ref() {
+		if prev.0.as_ref() != self.0.as_ref() {
+			let element = B::node_to_element(tree.closest_node()).unwrap();
+			tree.backend.remove_attribute(&element, prev.0.as_ref());
+			tree.backend
+				.set_attribute(&element, self.0.as_ref(), self.1.as_ref());
+		} else if prev.1.as_ref() != self.1.as_ref() {
 			let element = B::node_to_element(tree.closest_node()).unwrap();
 			tree.backend
 				.set_attribute(&element, self.0.as_ref(), self.1.as_ref());
@@ -97,3 +320,311 @@ impl<B: HtmlBackend, S1: StringLike, S2: StringLike> Markup<B> for Attr<S1, S2>
 		}
 	}
 }
+
+impl<B: HtmlBackend, S1: AttrName, S2: StringLike> Markup<B> for Attrs<S1, S2> {
+	fn has_own_node() -> bool {
+		false
+	}
+
+	fn dynamic() -> bool {
+		// The set of attributes is only known at runtime
+		true
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		let element = B::node_to_element(tree.closest_node()).unwrap();
+		for (name, value) in &self.0 {
+			claim_attribute(tree, name.as_ref());
+			tree.backend
+				.set_attribute(&element, name.as_ref(), value.as_ref())
+		}
+	}
+
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		let element = B::node_to_element(tree.closest_node()).unwrap();
+		for name in prev.0.keys() {
+			if !self.0.contains_key(name.as_ref()) {
+				tree.backend.remove_attribute(&element, name.as_ref());
+			}
+		}
+
+		for (name, value) in &self.0 {
+			let changed = prev
+				.0
+				.get(name.as_ref())
+				.map_or(true, |prev| prev.as_ref() != value.as_ref());
+
+			if changed {
+				tree.backend
+					.set_attribute(&element, name.as_ref(), value.as_ref());
+			}
+		}
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		if should_unmount {
+			let element = B::node_to_element(tree.closest_node()).unwrap();
+			for name in self.0.keys() {
+				tree.backend.remove_attribute(&element, name.as_ref());
+			}
+		}
+	}
+}
+
+/// Value that is the same in every render, attributes with it are never diffed,
+/// e.g. `input_type(Const(InputType::Checkbox))` or `bool_attr("hidden", Const(true))`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Const<V>(pub V);
+
+impl<V> Borrow<V> for Const<V> {
+	fn borrow(&self) -> &V {
+		&self.0
+	}
+}
+
+/// Value of a [`BoolAttr`]
+pub trait BoolValue: Copy + PartialEq {
+	const DYNAMIC: bool;
+
+	fn is_set(self) -> bool;
+}
+
+impl BoolValue for bool {
+	const DYNAMIC: bool = true;
+
+	fn is_set(self) -> bool {
+		self
+	}
+}
+
+impl BoolValue for Const<bool> {
+	const DYNAMIC: bool = false;
+
+	fn is_set(self) -> bool {
+		self.0
+	}
+}
+
+/// Attribute that is present when the value is `true`, e.g. `bool_attr("checked", item.is_done)`
+#[derive(Clone)]
+pub struct BoolAttr<S: StringLike, V: BoolValue = bool>(S, V);
+
+pub fn bool_attr<S: StringLike, V: BoolValue>(name: S, value: V) -> BoolAttr<S, V> {
+	BoolAttr(name, value)
+}
+
+impl<B: HtmlBackend, S: StringLike, V: BoolValue> Markup<B> for BoolAttr<S, V> {
+	fn has_own_node() -> bool {
+		false
+	}
+
+	fn dynamic() -> bool {
+		S::DYNAMIC || V::DYNAMIC
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		if self.1.is_set() {
+			claim_attribute(tree, self.0.as_ref());
+			let element = B::node_to_element(tree.closest_node()).unwrap();
+			tree.backend.set_attribute(&element, self.0.as_ref(), "")
+		}
+	}
+
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		if prev.0.as_ref() != self.0.as_ref() {
+			Markup::<B>::drop(prev, tree, true);
+			self.render(tree);
+		} else if prev.1 != self.1 {
+			let element = B::node_to_element(tree.closest_node()).unwrap();
+			if self.1.is_set() {
+				tree.backend.set_attribute(&element, self.0.as_ref(), "")
+			} else {
+				tree.backend.remove_attribute(&element, self.0.as_ref())
+			}
+		}
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		if should_unmount && self.1.is_set() {
+			let element = B::node_to_element(tree.closest_node()).unwrap();
+			tree.backend.remove_attribute(&element, self.0.as_ref());
+		}
+	}
+}
+
+/// Value of a [`TypedAttr`]
+pub trait AttrValue: PartialEq {
+	const DYNAMIC: bool;
+
+	/// Calls `func` with the attribute text, numbers are formatted on the stack
+	fn with_str<R>(&self, func: impl FnOnce(&str) -> R) -> R;
+}
+
+struct StackBuffer {
+	bytes: [u8; 32],
+	len: usize,
+}
+
+impl Write for StackBuffer {
+	fn write_str(&mut self, s: &str) -> std::fmt::Result {
+		let end = self.len + s.len();
+		if end > self.bytes.len() {
+			return Err(std::fmt::Error);
+		}
+
+		self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+		self.len = end;
+		Ok(())
+	}
+}
+
+fn with_display<R>(value: &impl Display, func: impl FnOnce(&str) -> R) -> R {
+	let mut buffer = StackBuffer {
+		bytes: [0; 32],
+		len: 0,
+	};
+
+	if write!(buffer, "{}", value).is_ok() {
+		func(std::str::from_utf8(&buffer.bytes[..buffer.len]).unwrap())
+	} else {
+		// e.g. `1e300f64` doesn't fit
+		func(&value.to_string())
+	}
+}
+
+macro_rules! numeric_attr_value {
+	($($ty:ty),*) => {
+		$(
+			impl AttrValue for $ty {
+				const DYNAMIC: bool = true;
+
+				fn with_str<R>(&self, func: impl FnOnce(&str) -> R) -> R {
+					with_display(self, func)
+				}
+			}
+		)*
+	};
+}
+
+numeric_attr_value!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+macro_rules! enum_attr_value {
+	($(#[$meta:meta])* $name:ident { $($variant:ident => $value:literal,)* }) => {
+		$(#[$meta])*
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+		pub enum $name {
+			$($variant,)*
+		}
+
+		impl $name {
+			pub const fn as_str(&self) -> &'static str {
+				match self {
+					$(Self::$variant => $value,)*
+				}
+			}
+		}
+
+		impl AttrValue for $name {
+			const DYNAMIC: bool = true;
+
+			fn with_str<R>(&self, func: impl FnOnce(&str) -> R) -> R {
+				func(self.as_str())
+			}
+		}
+	};
+}
+
+enum_attr_value! {
+	/// `type` of an `<input>`
+	InputType {
+		Button => "button",
+		Checkbox => "checkbox",
+		Color => "color",
+		Date => "date",
+		DatetimeLocal => "datetime-local",
+		Email => "email",
+		File => "file",
+		Hidden => "hidden",
+		Image => "image",
+		Month => "month",
+		Number => "number",
+		Password => "password",
+		Radio => "radio",
+		Range => "range",
+		Reset => "reset",
+		Search => "search",
+		Submit => "submit",
+		Tel => "tel",
+		Text => "text",
+		Time => "time",
+		Url => "url",
+		Week => "week",
+	}
+}
+
+enum_attr_value! {
+	/// `type` of a `<button>`
+	ButtonType {
+		Button => "button",
+		Reset => "reset",
+		Submit => "submit",
+	}
+}
+
+impl<V: AttrValue> AttrValue for Const<V> {
+	const DYNAMIC: bool = false;
+
+	fn with_str<R>(&self, func: impl FnOnce(&str) -> R) -> R {
+		self.0.with_str(func)
+	}
+}
+
+/// Attribute with a number or an enumerated value, e.g. `typed_attr("tabindex", 0)`
+#[derive(Clone)]
+pub struct TypedAttr<S: StringLike, V: AttrValue>(S, V);
+
+pub fn typed_attr<S: StringLike, V: AttrValue>(name: S, value: V) -> TypedAttr<S, V> {
+	TypedAttr(name, value)
+}
+
+pub fn input_type<V: AttrValue + Borrow<InputType>>(value: V) -> TypedAttr<&'static str, V> {
+	TypedAttr("type", value)
+}
+
+pub fn button_type<V: AttrValue + Borrow<ButtonType>>(value: V) -> TypedAttr<&'static str, V> {
+	TypedAttr("type", value)
+}
+
+impl<B: HtmlBackend, S: StringLike, V: AttrValue> Markup<B> for TypedAttr<S, V> {
+	fn has_own_node() -> bool {
+		false
+	}
+
+	fn dynamic() -> bool {
+		S::DYNAMIC || V::DYNAMIC
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		claim_attribute(tree, self.0.as_ref());
+		let element = B::node_to_element(tree.closest_node()).unwrap();
+		self.1
+			.with_str(|value| tree.backend.set_attribute(&element, self.0.as_ref(), value))
+	}
+
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		if prev.0.as_ref() != self.0.as_ref() {
+			let element = B::node_to_element(tree.closest_node()).unwrap();
+			tree.backend.remove_attribute(&element, prev.0.as_ref());
+			self.render(tree);
+		} else if prev.1 != self.1 {
+			self.render(tree);
+		}
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		if should_unmount {
+			let element = B::node_to_element(tree.closest_node()).unwrap();
+			tree.backend.remove_attribute(&element, self.0.as_ref());
+		}
+	}
+}
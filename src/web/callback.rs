@@ -1,20 +1,21 @@
 use std::any::TypeId;
 use std::hash::Hash;
-use std::marker::{PhantomData, Unsize};
+use std::marker::{Tuple, Unsize};
 use std::ops::Deref;
+use std::panic::Location;
 use std::rc::Rc;
 
-use super::context::{Extension, StatefulContext, WithMemo};
+use super::context::{call_site_key, explicit_key, Extension, StatefulContext, WithMemo};
 use super::event::EventCallback;
 use crate::Backend;
 
-pub struct Callback<T: ?Sized>(pub Rc<T>, TypeId);
+pub struct Callback<T: ?Sized>(pub Rc<T>, pub(crate) TypeId);
 
-impl<T> EventCallback for Callback<T>
+impl<E, T> EventCallback<E> for Callback<T>
 where
-	T: Fn(web_sys::Event) + 'static,
+	T: Fn(E) + 'static,
 {
-	fn call(&self, event: web_sys::Event) {
+	fn call(&self, event: E) {
 		(self.0)(event)
 	}
 
@@ -27,8 +28,8 @@ where
 	}
 }
 
-impl EventCallback for Callback<dyn Fn(web_sys::Event)> {
-	fn call(&self, event: web_sys::Event) {
+impl<E: 'static> EventCallback<E> for Callback<dyn Fn(E)> {
+	fn call(&self, event: E) {
 		(self.0)(event)
 	}
 
@@ -41,8 +42,8 @@ impl EventCallback for Callback<dyn Fn(web_sys::Event)> {
 	}
 }
 
-impl EventCallback for Callback<dyn Fn()> {
-	fn call(&self, _: web_sys::Event) {
+impl<E> EventCallback<E> for Callback<dyn Fn()> {
+	fn call(&self, _: E) {
 		(self.0)()
 	}
 
@@ -105,101 +106,130 @@ impl<T: ?Sized> Deref for Callback<T> {
 
 impl<T: ?Sized> Eq for Callback<T> {}
 
-struct Callback0<F, R, M>
-where
-	F: Fn() -> R,
-{
+/// Function stored together with the memo value it was created with
+pub struct Memoized<F, M> {
 	func: F,
 	memo: M,
 }
 
-impl<F, R, M> Fn<()> for Callback0<F, R, M>
+impl<F, M, Args> Fn<Args> for Memoized<F, M>
 where
-	F: Fn() -> R,
-	R: Default,
+	F: Fn<Args>,
+	Args: Tuple,
 {
-	extern "rust-call" fn call(&self, _args: ()) -> R {
-		(self.func)()
+	extern "rust-call" fn call(&self, args: Args) -> F::Output {
+		self.func.call(args)
 	}
 }
 
-impl<F, R, M> FnMut<()> for Callback0<F, R, M>
+impl<F, M, Args> FnMut<Args> for Memoized<F, M>
 where
-	F: Fn() -> R,
-	R: Default,
+	F: Fn<Args>,
+	Args: Tuple,
 {
-	extern "rust-call" fn call_mut(&mut self, args: ()) -> R {
-		self.call(args)
+	extern "rust-call" fn call_mut(&mut self, args: Args) -> F::Output {
+		self.func.call(args)
 	}
 }
 
-impl<F, R, M> FnOnce<()> for Callback0<F, R, M>
+impl<F, M, Args> FnOnce<Args> for Memoized<F, M>
 where
-	F: Fn() -> R,
-	R: Default,
+	F: Fn<Args>,
+	Args: Tuple,
 {
-	extern "rust-call" fn call_once(self, args: ()) -> R {
-		self.call(args)
-	}
-
-	type Output = R;
-}
+	type Output = F::Output;
 
-struct Callback1<F, R, T, M>
-where
-	F: Fn(T) -> R,
-{
-	func: F,
-	memo: M,
-	_t: PhantomData<T>,
+	extern "rust-call" fn call_once(self, args: Args) -> F::Output {
+		self.func.call(args)
+	}
 }
 
-impl<F, R, T, M> Fn<(T,)> for Callback1<F, R, T, M>
+impl<B: Backend + 'static, E: 'static> StatefulContext<B, E>
 where
-	F: Fn(T) -> R,
-	R: Default,
+	E: Extension<WithMemo>,
 {
-	extern "rust-call" fn call(&self, args: (T,)) -> R {
-		(self.func)(args.0)
+	/// Memoizes a function of any arity, the function is replaced when `memo` changes.
+	///
+	/// ```ignore
+	/// let on_select = cx.callback_eq(index, cx.wrap(move |cx, (ev,): (MouseEvent,)| {
+	///     cx.dispatch(Select(index, ev.shift_key()))
+	/// }));
+	/// ```
+	#[track_caller]
+	pub fn callback_eq<Args, F, R, M>(&self, memo: M, func: F) -> Callback<dyn Fn<Args, Output = R>>
+	where
+		Args: Tuple + 'static,
+		F: Fn<Args, Output = R> + 'static,
+		R: 'static,
+		M: PartialEq + 'static,
+	{
+		let key = call_site_key::<Memoized<F, M>>();
+		self.callback_internal(key, memo, func)
 	}
-}
 
-impl<F, R, T, M> FnMut<(T,)> for Callback1<F, R, T, M>
-where
-	F: Fn(T) -> R,
-	R: Default,
-{
-	extern "rust-call" fn call_mut(&mut self, args: (T,)) -> R {
-		self.call(args)
+	#[track_caller]
+	pub fn callback<Args, F, R>(&self, func: F) -> Callback<dyn Fn<Args, Output = R>>
+	where
+		Args: Tuple + 'static,
+		F: Fn<Args, Output = R> + 'static,
+		R: 'static,
+	{
+		self.callback_eq((), func)
 	}
-}
 
-impl<F, R, T, M> FnOnce<(T,)> for Callback1<F, R, T, M>
-where
-	F: Fn(T) -> R,
-	R: Default,
-{
-	extern "rust-call" fn call_once(self, args: (T,)) -> R {
-		self.call(args)
+	#[track_caller]
+	pub fn callback_hash<Args, F, R, M>(
+		&self,
+		memo: &M,
+		func: F,
+	) -> Callback<dyn Fn<Args, Output = R>>
+	where
+		Args: Tuple + 'static,
+		F: Fn<Args, Output = R> + 'static,
+		R: 'static,
+		M: Hash + ?Sized,
+	{
+		self.callback_eq(fxhash::hash64(memo), func)
 	}
 
-	type Output = R;
-}
+	/// Same as [`StatefulContext::callback_eq`], but keyed explicitly instead of by the call site
+	#[track_caller]
+	pub fn callback_with_key<K, Args, F, R, M>(
+		&self,
+		key: &K,
+		memo: M,
+		func: F,
+	) -> Callback<dyn Fn<Args, Output = R>>
+	where
+		K: Hash + ?Sized,
+		Args: Tuple + 'static,
+		F: Fn<Args, Output = R> + 'static,
+		R: 'static,
+		M: PartialEq + 'static,
+	{
+		let key = explicit_key::<Memoized<F, M>, K>(key);
+		self.callback_internal(key, memo, func)
+	}
 
-impl<B: Backend + 'static, E: 'static> StatefulContext<B, E>
-where
-	E: Extension<WithMemo>,
-{
-	pub fn callback_0_eq<F, R, M>(&self, memo: M, func: F) -> Callback<dyn Fn() -> R>
+	#[track_caller]
+	fn callback_internal<Args, F, R, M>(
+		&self,
+		key: u64,
+		memo: M,
+		func: F,
+	) -> Callback<dyn Fn<Args, Output = R>>
 	where
+		Args: Tuple + 'static,
+		F: Fn<Args, Output = R> + 'static,
+		R: 'static,
 		M: PartialEq + 'static,
-		R: Default + 'static,
-		F: Fn() -> R + 'static,
 	{
 		let type_id = TypeId::of::<F>();
 		let with_memo: &WithMemo = self.ext.get();
-		if let Some(cb) = with_memo.memo.borrow_mut().try_dyn_with_type_id(type_id) {
-			let callback = Rc::downcast::<Callback0<F, R, M>>(cb.clone())
+		with_memo.check_key(key, Location::caller());
+
+		if let Some(cb) = with_memo.memo.borrow_mut().try_dyn_with_key(key) {
+			let callback = Rc::downcast::<Memoized<F, M>>(cb.clone())
 				.map_err(|_| ())
 				.unwrap();
 
@@ -208,72 +238,69 @@ where
 			}
 		}
 
-		let callback = Rc::new(Callback0 { func, memo });
+		let callback = Rc::new(Memoized { func, memo });
 
 		with_memo
 			.memo
 			.borrow_mut()
-			.set_dyn_with_type_id(type_id, callback.clone());
+			.set_dyn_with_key(key, callback.clone());
 
 		Callback(callback, type_id)
 	}
 
+	#[track_caller]
+	#[deprecated(note = "Use `callback_eq` instead")]
+	pub fn callback_0_eq<F, R, M>(&self, memo: M, func: F) -> Callback<dyn Fn() -> R>
+	where
+		M: PartialEq + 'static,
+		R: 'static,
+		F: Fn() -> R + 'static,
+	{
+		self.callback_eq(memo, func)
+	}
+
+	#[track_caller]
+	#[deprecated(note = "Use `callback` instead")]
 	pub fn callback_0<F>(&self, func: F) -> Callback<dyn Fn()>
 	where
 		F: Fn() + 'static,
 	{
-		self.callback_0_eq((), func)
+		self.callback_eq((), func)
 	}
 
+	#[track_caller]
+	#[deprecated(note = "Use `callback_hash` instead")]
 	pub fn callback_0_hash<F, M>(&self, memo: &M, func: F) -> Callback<dyn Fn()>
 	where
 		M: Hash,
 		F: Fn() + 'static,
 	{
-		self.callback_0_eq(fxhash::hash64(memo), func)
+		self.callback_hash(memo, func)
 	}
 
+	#[track_caller]
+	#[deprecated(note = "Use `callback_eq` instead")]
 	pub fn callback_1_eq<F, R, M, T: 'static>(&self, memo: M, func: F) -> Callback<dyn Fn(T) -> R>
 	where
 		M: PartialEq + 'static,
-		R: Default + 'static,
+		R: 'static,
 		F: Fn(T) -> R + 'static,
 	{
-		let type_id = TypeId::of::<F>();
-
-		let with_memo: &WithMemo = self.ext.get();
-		if let Some(cb) = with_memo.memo.borrow_mut().try_dyn_with_type_id(type_id) {
-			let callback = Rc::downcast::<Callback1<F, R, T, M>>(cb.clone())
-				.map_err(|_| ())
-				.unwrap();
-
-			if callback.memo == memo {
-				return Callback(callback, type_id);
-			}
-		}
-
-		let callback = Rc::new(Callback1 {
-			func,
-			memo,
-			_t: PhantomData,
-		});
-
-		with_memo
-			.memo
-			.borrow_mut()
-			.set_dyn_with_type_id(type_id, callback.clone());
-
-		Callback(callback, type_id)
+		self.callback_eq(memo, func)
 	}
 
+	#[track_caller]
+	#[deprecated(note = "Use `callback` instead")]
 	pub fn callback_1<F, R, T: 'static>(&self, func: F) -> Callback<dyn Fn(T) -> R>
 	where
 		F: Fn(T) -> R + 'static,
-		R: Default + 'static,
+		R: 'static,
 	{
-		self.callback_1_eq((), func)
+		self.callback_eq((), func)
 	}
 
+	#[track_caller]
+	#[deprecated(note = "Use `callback_hash` instead")]
 	pub fn callback_1_hash<F, M, R, T: 'static>(
 		&self,
 		memo: &M,
@@ -281,9 +308,9 @@ where
 	) -> Callback<dyn Fn(T) -> R>
 	where
 		M: Hash,
-		R: Default + 'static,
+		R: 'static,
 		F: Fn(T) -> R + 'static,
 	{
-		self.callback_1_eq(fxhash::hash64(memo), func)
+		self.callback_hash(memo, func)
 	}
 }
@@ -0,0 +1,231 @@
+//! Time source of scheduled callbacks.
+//!
+//! [`BrowserClock`] uses `setTimeout` and `requestAnimationFrame`,
+//! [`ManualClock`] is advanced by hand and makes timers testable natively.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::JsCast;
+
+use super::helpers::dom::WINDOW;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u32);
+
+pub trait Clock {
+	/// Current time in milliseconds
+	fn now(&self) -> f64;
+
+	/// Calls `func` after `delay` milliseconds
+	fn set_timeout(&self, delay: u32, func: Box<dyn FnOnce()>) -> TimerId;
+
+	/// Calls `func` before the next repaint
+	fn request_frame(&self, func: Box<dyn FnOnce()>) -> TimerId;
+
+	/// Does nothing if the timer has already fired
+	fn cancel(&self, id: TimerId);
+}
+
+/// Clock of a subtree, provided with [`provide`](super::dispatch::provide).
+/// Components without one use [`BrowserClock`], which is only available on wasm.
+#[derive(Clone)]
+pub struct SharedClock(pub Rc<dyn Clock>);
+
+impl SharedClock {
+	pub fn new(clock: impl Clock + 'static) -> Rc<Self> {
+		Rc::new(SharedClock(Rc::new(clock)))
+	}
+}
+
+struct BrowserTimer {
+	handle: i32,
+	frame: bool,
+	// Keeps the JS function alive until it's called or cancelled
+	_closure: Closure<dyn FnMut()>,
+}
+
+#[derive(Clone, Default)]
+pub struct BrowserClock {
+	next_id: Rc<Cell<u32>>,
+	timers: Rc<RefCell<HashMap<u32, BrowserTimer>>>,
+}
+
+impl BrowserClock {
+	fn closure(&self, id: u32, func: Box<dyn FnOnce()>) -> Closure<dyn FnMut()> {
+		let timers = Rc::downgrade(&self.timers);
+		Closure::once(move || {
+			// Dropped after the call, wasm-bindgen defers freeing the running closure
+			let _timer = Weak::upgrade(&timers).and_then(|t| t.borrow_mut().remove(&id));
+			func()
+		})
+	}
+
+	fn next_id(&self) -> u32 {
+		let id = self.next_id.get();
+		self.next_id.set(id + 1);
+		id
+	}
+}
+
+impl Clock for BrowserClock {
+	fn now(&self) -> f64 {
+		js_sys::Date::now()
+	}
+
+	fn set_timeout(&self, delay: u32, func: Box<dyn FnOnce()>) -> TimerId {
+		let id = self.next_id();
+		let closure = self.closure(id, func);
+		let handle = WINDOW.with(|w| {
+			w.set_timeout_with_callback_and_timeout_and_arguments_0(
+				closure.as_ref().unchecked_ref(),
+				delay as i32,
+			)
+			.unwrap()
+		});
+
+		self.timers.borrow_mut().insert(
+			id,
+			BrowserTimer {
+				handle,
+				frame: false,
+				_closure: closure,
+			},
+		);
+
+		TimerId(id)
+	}
+
+	fn request_frame(&self, func: Box<dyn FnOnce()>) -> TimerId {
+		let id = self.next_id();
+		let closure = self.closure(id, func);
+		let handle = WINDOW.with(|w| {
+			w.request_animation_frame(closure.as_ref().unchecked_ref())
+				.unwrap()
+		});
+
+		self.timers.borrow_mut().insert(
+			id,
+			BrowserTimer {
+				handle,
+				frame: true,
+				_closure: closure,
+			},
+		);
+
+		TimerId(id)
+	}
+
+	fn cancel(&self, id: TimerId) {
+		let timer = self.timers.borrow_mut().remove(&id.0);
+		if let Some(timer) = timer {
+			WINDOW.with(|w| {
+				if timer.frame {
+					let _ = w.cancel_animation_frame(timer.handle);
+				} else {
+					w.clear_timeout_with_handle(timer.handle);
+				}
+			})
+		}
+	}
+}
+
+struct ManualTimer {
+	id: u32,
+	// `None` for animation frames
+	at: Option<f64>,
+	func: Box<dyn FnOnce()>,
+}
+
+#[derive(Default)]
+struct ManualClockInner {
+	now: Cell<f64>,
+	next_id: Cell<u32>,
+	timers: RefCell<Vec<ManualTimer>>,
+}
+
+/// Clock that only moves when told to
+#[derive(Clone, Default)]
+pub struct ManualClock(Rc<ManualClockInner>);
+
+impl ManualClock {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Moves the time forward, firing due timers in order
+	pub fn advance(&self, ms: f64) {
+		let until = self.0.now.get() + ms;
+		loop {
+			let next = {
+				let mut timers = self.0.timers.borrow_mut();
+				let due = timers
+					.iter()
+					.enumerate()
+					.filter_map(|(i, t)| t.at.filter(|at| *at <= until).map(|at| (i, at)))
+					.min_by(|a, b| a.1.total_cmp(&b.1));
+
+				due.map(|(i, at)| (timers.remove(i), at))
+			};
+
+			match next {
+				Some((timer, at)) => {
+					self.0.now.set(at);
+					(timer.func)();
+				}
+				None => break,
+			}
+		}
+
+		self.0.now.set(until);
+	}
+
+	/// Fires the animation frame callbacks requested so far
+	pub fn frame(&self) {
+		let frames: Vec<ManualTimer> = {
+			let mut timers = self.0.timers.borrow_mut();
+			let (frames, rest): (Vec<_>, Vec<_>) = timers.drain(..).partition(|t| t.at.is_none());
+			*timers = rest;
+			frames
+		};
+
+		for frame in frames {
+			(frame.func)();
+		}
+	}
+
+	/// Number of timers and frames that haven't fired yet
+	pub fn pending(&self) -> usize {
+		self.0.timers.borrow().len()
+	}
+
+	fn add(&self, at: Option<f64>, func: Box<dyn FnOnce()>) -> TimerId {
+		let id = self.0.next_id.get();
+		self.0.next_id.set(id + 1);
+		self.0
+			.timers
+			.borrow_mut()
+			.push(ManualTimer { id, at, func });
+		TimerId(id)
+	}
+}
+
+impl Clock for ManualClock {
+	fn now(&self) -> f64 {
+		self.0.now.get()
+	}
+
+	fn set_timeout(&self, delay: u32, func: Box<dyn FnOnce()>) -> TimerId {
+		self.add(Some(self.now() + delay as f64), func)
+	}
+
+	fn request_frame(&self, func: Box<dyn FnOnce()>) -> TimerId {
+		self.add(None, func)
+	}
+
+	fn cancel(&self, id: TimerId) {
+		self.0.timers.borrow_mut().retain(|t| t.id != id.0);
+	}
+}
@@ -1,8 +1,10 @@
 use std::any::{Any, TypeId};
 use std::cell::{RefCell, RefMut};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Request;
-use std::marker::PhantomData;
+use std::hash::Hash;
+use std::marker::{PhantomData, Tuple};
+use std::panic::Location;
 use std::rc::Weak;
 
 use bumpalo::Bump;
@@ -51,6 +53,55 @@ pub struct WithArena {
 #[derive(Default)]
 pub struct WithMemo {
 	pub(crate) memo: RefCell<AnyData>,
+	// Keys used during the current render
+	keys: RefCell<HashSet<u64>>,
+}
+
+impl WithMemo {
+	/// Should be called before the component function runs again
+	pub(crate) fn reset_keys(&self) {
+		self.keys.borrow_mut().clear();
+	}
+
+	/// Reports hooks that share a slot within one render
+	pub(crate) fn check_key(&self, key: u64, location: &Location) {
+		if !self.keys.borrow_mut().insert(key) && cfg!(debug_assertions) {
+			report_duplicate_key(location);
+		}
+	}
+
+	/// Drops the slots of hooks that were not called during the last render, e.g. their key is gone
+	pub(crate) fn drop_unused(&self) {
+		let keys = self.keys.borrow();
+		self.memo.borrow_mut().retain(|key| keys.contains(&key));
+	}
+}
+
+pub(crate) fn report_duplicate_key(location: &Location) {
+	tracing::warn!(
+		"Hook at {} reuses the slot of another hook in the same render, use a `_with_key` variant",
+		location
+	);
+}
+
+/// Storage key of a hook, derived from the place where the hook was called.
+///
+/// `T` is the type of the stored value, so that hooks of different types never
+/// share a slot even when a helper without `#[track_caller]` calls them.
+#[track_caller]
+pub(crate) fn call_site_key<T: 'static>() -> u64 {
+	let location = Location::caller();
+	fxhash::hash64(&(
+		TypeId::of::<T>(),
+		location.file(),
+		location.line(),
+		location.column(),
+	))
+}
+
+/// Storage key of a hook with an explicit key, e.g. for hooks called in a loop
+pub(crate) fn explicit_key<T: 'static, K: Hash + ?Sized>(key: &K) -> u64 {
+	fxhash::hash64(&(TypeId::of::<T>(), fxhash::hash64(key)))
 }
 
 #[derive(Default)]
@@ -59,7 +110,7 @@ pub struct WithState {
 }
 
 pub struct WithEffects<B, E> {
-	pub(crate) effects: RefCell<IndexMap<TypeId, EffectContext<B, E>>>,
+	pub(crate) effects: RefCell<IndexMap<u64, EffectContext<B, E>>>,
 }
 
 impl<B, E> Default for WithEffects<B, E> {
@@ -133,6 +184,16 @@ impl<B> MaybeExtension<WithEffects<B, Self>> for DefaultExt<B> {
 	}
 }
 
+impl<B> MaybeExtension<WithMemo> for DefaultExt<B> {
+	fn try_get(&self) -> Option<&WithMemo> {
+		Some(&self.memo)
+	}
+
+	fn try_get_mut(&mut self) -> Option<&mut WithMemo> {
+		Some(&mut self.memo)
+	}
+}
+
 impl<B> MaybeExtension<WithArena> for DefaultExt<B> {
 	fn try_get(&self) -> Option<&WithArena> {
 		None
@@ -206,13 +267,37 @@ impl<B: Backend + 'static, E: 'static> StatefulContext<B, E> {
 		None
 	}
 
-	pub fn memo<T: Envelope, F: FnOnce() -> T + 'static>(&mut self, func: F) -> T::Output
+	#[track_caller]
+	pub fn memo<T: Envelope, F: FnOnce() -> T>(&mut self, func: F) -> T::Output
+	where
+		E: Extension<WithMemo>,
+	{
+		let key = call_site_key::<T>();
+		self.memo_internal(key, func)
+	}
+
+	/// Same as [`StatefulContext::memo`], but keyed explicitly instead of by the call site
+	#[track_caller]
+	pub fn memo_with_key<K, T, F>(&mut self, key: &K, func: F) -> T::Output
+	where
+		E: Extension<WithMemo>,
+		K: Hash + ?Sized,
+		T: Envelope,
+		F: FnOnce() -> T,
+	{
+		let key = explicit_key::<T, K>(key);
+		self.memo_internal(key, func)
+	}
+
+	#[track_caller]
+	fn memo_internal<T: Envelope, F: FnOnce() -> T>(&mut self, key: u64, func: F) -> T::Output
 	where
 		E: Extension<WithMemo>,
 	{
 		let with_memo: &WithMemo = self.ext.get();
+		with_memo.check_key(key, Location::caller());
+
 		let mut memo = with_memo.memo.borrow_mut();
-		let key = fxhash::hash64(&(TypeId::of::<T>(), TypeId::of::<F>()));
 		if let Some(item) = memo.try_with_key::<T>(key) {
 			item
 		} else {
@@ -222,14 +307,36 @@ impl<B: Backend + 'static, E: 'static> StatefulContext<B, E> {
 		}
 	}
 
-	pub fn memo_ref<T: Envelope, F: FnOnce() -> T + 'static>(&mut self, func: F) -> MemoRef<T>
+	#[track_caller]
+	pub fn memo_ref<T: Envelope, F: FnOnce() -> T>(&mut self, func: F) -> MemoRef<T>
+	where
+		E: Extension<WithMemo>,
+	{
+		let key = call_site_key::<T>();
+		self.memo_ref_internal(key, func)
+	}
+
+	#[track_caller]
+	pub fn memo_ref_with_key<K, T, F>(&mut self, key: &K, func: F) -> MemoRef<T>
+	where
+		E: Extension<WithMemo>,
+		K: Hash + ?Sized,
+		T: Envelope,
+		F: FnOnce() -> T,
+	{
+		let key = explicit_key::<T, K>(key);
+		self.memo_ref_internal(key, func)
+	}
+
+	#[track_caller]
+	fn memo_ref_internal<T: Envelope, F: FnOnce() -> T>(&mut self, key: u64, func: F) -> MemoRef<T>
 	where
 		E: Extension<WithMemo>,
 	{
 		let with_memo: &WithMemo = self.ext.get();
-		let mut memo = with_memo.memo.borrow_mut();
-		let key = fxhash::hash64(&(TypeId::of::<T>(), TypeId::of::<F>()));
+		with_memo.check_key(key, Location::caller());
 
+		let mut memo = with_memo.memo.borrow_mut();
 		if memo.try_with_key::<T>(key).is_none() {
 			let t = func();
 			memo.set_with_key::<T>(key, t);
@@ -241,6 +348,29 @@ impl<B: Backend + 'static, E: 'static> StatefulContext<B, E> {
 		}
 	}
 
+	/// Gives a function access to the context when it's called later, e.g. from an event handler.
+	///
+	/// Arguments are passed to `func` as a tuple, the result is a function of the same arity:
+	///
+	/// ```ignore
+	/// let on_item = cx.wrap(|cx, (index, ev): (usize, MouseEvent)| { ... });
+	/// on_item(0, ev);
+	/// ```
+	///
+	/// Returns `None` if the component is already gone.
+	pub fn wrap<Args, F, R>(&self, func: F) -> impl Fn<Args, Output = Option<R>>
+	where
+		Args: Tuple,
+		F: Fn(&mut Self, Args) -> R,
+		E: Extension<WithCycle<B, E>>,
+	{
+		Wrapped {
+			func,
+			this: self.ext.get().this.clone(),
+			_f: PhantomData,
+		}
+	}
+
 	pub fn wrap_0<F>(&self, func: F) -> impl Fn()
 	where
 		F: Fn(&mut Self),
@@ -283,6 +413,50 @@ impl<B: Backend + 'static, E: 'static> StatefulContext<B, E> {
 	}
 }
 
+/// See [`StatefulContext::wrap`]
+struct Wrapped<F, B: Backend, E, Args, R> {
+	func: F,
+	this: Weak<dyn HasContext<B, E>>,
+	_f: PhantomData<fn(Args) -> R>,
+}
+
+impl<F, B, E, Args, R> Fn<Args> for Wrapped<F, B, E, Args, R>
+where
+	F: Fn(&mut StatefulContext<B, E>, Args) -> R,
+	B: Backend,
+	Args: Tuple,
+{
+	extern "rust-call" fn call(&self, args: Args) -> Option<R> {
+		let this = self.this.upgrade()?;
+		let result = (self.func)(&mut this.context(), args);
+		Some(result)
+	}
+}
+
+impl<F, B, E, Args, R> FnMut<Args> for Wrapped<F, B, E, Args, R>
+where
+	F: Fn(&mut StatefulContext<B, E>, Args) -> R,
+	B: Backend,
+	Args: Tuple,
+{
+	extern "rust-call" fn call_mut(&mut self, args: Args) -> Option<R> {
+		self.call(args)
+	}
+}
+
+impl<F, B, E, Args, R> FnOnce<Args> for Wrapped<F, B, E, Args, R>
+where
+	F: Fn(&mut StatefulContext<B, E>, Args) -> R,
+	B: Backend,
+	Args: Tuple,
+{
+	type Output = Option<R>;
+
+	extern "rust-call" fn call_once(self, args: Args) -> Option<R> {
+		self.call(args)
+	}
+}
+
 // trait TyEq {}
 
 // impl<T> TyEq for (T, T) {}
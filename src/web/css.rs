@@ -0,0 +1,319 @@
+//! Scoped styles.
+//!
+//! [`css!`] turns a block of CSS into a [`Stylesheet`] with a class name derived
+//! from the hash of the source. Used as markup, the stylesheet adds the class to the
+//! element and mounts a `<style>` element for as long as any element uses it:
+//!
+//! ```ignore
+//! div((
+//!     css!("padding: 4px; &:hover { color: red; } & > span { font-weight: bold; }"),
+//!     span("Hello"),
+//! ))
+//! ```
+//!
+//! Top-level declarations apply to the element itself, `&` in nested selectors
+//! stands for the element. Selectors without `&` match descendants.
+
+use std::collections::HashMap;
+
+use web_sys::Element;
+
+use super::attr::{add_classes, remove_classes};
+use super::helpers::dom::DOCUMENT;
+use super::HtmlBackend;
+use crate::tree::Tree;
+use crate::Markup;
+
+/// See the [module documentation](self)
+pub macro css($source:literal) {{
+	static SHEET: $crate::web::css::Stylesheet = $crate::web::css::Stylesheet::new($source);
+	&SHEET
+}}
+
+/// Marks `<style>` elements of stylesheets, so that the client adopts the ones inlined by SSR
+pub const STYLE_ATTRIBUTE: &str = "data-skima-style";
+
+const PREFIX: &[u8] = b"sk-";
+const CLASS_LEN: usize = PREFIX.len() + 16;
+
+#[derive(Debug)]
+pub struct Stylesheet {
+	source: &'static str,
+	hash: u64,
+	class: [u8; CLASS_LEN],
+}
+
+impl Stylesheet {
+	pub const fn new(source: &'static str) -> Self {
+		let hash = fnv1a(source.as_bytes());
+		Stylesheet {
+			source,
+			hash,
+			class: class_name(hash),
+		}
+	}
+
+	pub fn hash(&self) -> u64 {
+		self.hash
+	}
+
+	/// Scoped class name, e.g. `sk-af63bd4c8601b7df`
+	pub fn class(&self) -> &str {
+		std::str::from_utf8(&self.class).unwrap()
+	}
+
+	/// CSS with the selectors scoped to [`Stylesheet::class`]
+	pub fn to_css(&self) -> String {
+		scope(self.source, &format!(".{}", self.class()))
+	}
+}
+
+// FNV-1a, stable across builds and targets so that SSR and the client agree on class names
+const fn fnv1a(bytes: &[u8]) -> u64 {
+	let mut hash: u64 = 0xcbf29ce484222325;
+	let mut i = 0;
+	while i < bytes.len() {
+		hash ^= bytes[i] as u64;
+		hash = hash.wrapping_mul(0x100000001b3);
+		i += 1;
+	}
+	hash
+}
+
+const fn class_name(hash: u64) -> [u8; CLASS_LEN] {
+	const HEX: &[u8; 16] = b"0123456789abcdef";
+
+	let mut class = [0u8; CLASS_LEN];
+	let mut i = 0;
+	while i < PREFIX.len() {
+		class[i] = PREFIX[i];
+		i += 1;
+	}
+
+	let mut digit = 0;
+	while digit < 16 {
+		class[PREFIX.len() + digit] = HEX[((hash >> ((15 - digit) * 4)) & 0xf) as usize];
+		digit += 1;
+	}
+
+	class
+}
+
+fn scope(source: &str, selector: &str) -> String {
+	let mut declarations = String::new();
+	let mut rules = String::new();
+
+	let mut depth = 0;
+	let mut start = 0;
+	// Braces and semicolons in strings and `url(..)` are not part of the structure
+	let mut quote = None;
+	let mut escaped = false;
+	let mut parens = 0;
+	for (i, c) in source.char_indices() {
+		if let Some(q) = quote {
+			if escaped {
+				escaped = false;
+			} else if c == '\\' {
+				escaped = true;
+			} else if c == q {
+				quote = None;
+			}
+			continue;
+		}
+
+		match c {
+			'"' | '\'' => quote = Some(c),
+			'(' => parens += 1,
+			')' => parens -= 1,
+			_ if parens > 0 => {}
+			'{' => depth += 1,
+			'}' => {
+				depth -= 1;
+				if depth == 0 {
+					rules.push_str(&scope_rule(source[start..=i].trim(), selector));
+					start = i + 1;
+				}
+			}
+			';' if depth == 0 => {
+				declarations.push_str(source[start..=i].trim());
+				start = i + 1;
+			}
+			_ => {}
+		}
+	}
+
+	let tail = source[start..].trim();
+	if !tail.is_empty() {
+		declarations.push_str(tail);
+		declarations.push(';');
+	}
+
+	if declarations.is_empty() {
+		rules
+	} else {
+		format!("{}{{{}}}{}", selector, declarations, rules)
+	}
+}
+
+fn scope_rule(rule: &str, selector: &str) -> String {
+	let (head, body) = rule.split_at(rule.find('{').unwrap());
+	let head = head.trim();
+
+	// `@media` and friends contain a nested block in the same format
+	if head.starts_with('@') {
+		let inner = &body[1..body.len() - 1];
+		return format!("{}{{{}}}", head, scope(inner, selector));
+	}
+
+	let selectors: Vec<String> = head
+		.split(',')
+		.map(|s| {
+			let s = s.trim();
+			if s.contains('&') {
+				s.replace('&', selector)
+			} else {
+				format!("{} {}", selector, s)
+			}
+		})
+		.collect();
+
+	format!("{}{}", selectors.join(","), body)
+}
+
+/// Reference counts of mounted stylesheets, `T` is whatever the backend
+/// needs to remove the stylesheet, e.g. the `<style>` element.
+#[derive(Debug)]
+pub struct StyleRegistry<T> {
+	mounted: HashMap<u64, (usize, T)>,
+}
+
+impl<T> Default for StyleRegistry<T> {
+	fn default() -> Self {
+		Self {
+			mounted: Default::default(),
+		}
+	}
+}
+
+impl<T> StyleRegistry<T> {
+	/// `mount` is only called for the first user of the stylesheet
+	pub fn acquire(&mut self, sheet: &Stylesheet, mount: impl FnOnce() -> T) {
+		self.mounted
+			.entry(sheet.hash)
+			.and_modify(|(count, _)| *count += 1)
+			.or_insert_with(|| (1, mount()));
+	}
+
+	/// Returns the mounted value when the last user is gone
+	pub fn release(&mut self, sheet: &Stylesheet) -> Option<T> {
+		let (count, _) = self.mounted.get_mut(&sheet.hash)?;
+		*count -= 1;
+		if *count == 0 {
+			self.mounted.remove(&sheet.hash).map(|(_, value)| value)
+		} else {
+			None
+		}
+	}
+
+	pub fn count(&self, sheet: &Stylesheet) -> usize {
+		self.mounted.get(&sheet.hash).map_or(0, |(count, _)| *count)
+	}
+}
+
+pub(crate) fn mount_style_element(sheet: &Stylesheet) -> Element {
+	DOCUMENT.with(|document| {
+		let selector = format!("style[{}=\"{}\"]", STYLE_ATTRIBUTE, sheet.class());
+		if let Some(element) = document.query_selector(&selector).ok().flatten() {
+			return element;
+		}
+
+		let element = document.create_element("style").unwrap();
+		element
+			.set_attribute(STYLE_ATTRIBUTE, sheet.class())
+			.unwrap();
+		element.set_text_content(Some(&sheet.to_css()));
+		document.head().unwrap().append_child(&element).unwrap();
+		element
+	})
+}
+
+impl<B: HtmlBackend> Markup<B> for &'static Stylesheet {
+	fn has_own_node() -> bool {
+		false
+	}
+
+	fn dynamic() -> bool {
+		true
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		add_classes(tree, [self.class()]);
+		tree.backend.mount_stylesheet(*self);
+	}
+
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		if !std::ptr::eq(*self, *prev) {
+			Markup::<B>::drop(prev, tree, true);
+			self.render(tree);
+		}
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		if should_unmount {
+			remove_classes(tree, [self.class()]);
+		}
+
+		tree.backend.unmount_stylesheet(*self);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_scope() {
+		let sheet = Stylesheet::new(
+			"color: red; &:hover, &.active { color: blue; } span { margin: 0 } @media print { display: none }",
+		);
+
+		let class = sheet.class();
+		assert_eq!(class.len(), CLASS_LEN);
+		assert_eq!(
+			sheet.to_css(),
+			format!(
+				".{c}{{color: red;}}.{c}:hover,.{c}.active{{ color: blue; }}.{c} span{{ margin: 0 }}@media print{{.{c}{{display: none;}}}}",
+				c = class
+			)
+		);
+	}
+
+	#[test]
+	fn test_scope_strings() {
+		let sheet = Stylesheet::new(
+			r#"content: "}"; background: url(data:image/png;base64,AA); &::after { content: ';{' }"#,
+		);
+
+		assert_eq!(
+			sheet.to_css(),
+			format!(
+				r#".{c}{{content: "}}";background: url(data:image/png;base64,AA);}}.{c}::after{{ content: ';{{' }}"#,
+				c = sheet.class()
+			)
+		);
+	}
+
+	#[test]
+	fn test_registry() {
+		static SHEET: Stylesheet = Stylesheet::new("color: red");
+
+		let mut registry = StyleRegistry::default();
+		registry.acquire(&SHEET, || "style");
+		registry.acquire(&SHEET, || unreachable!());
+		assert_eq!(registry.count(&SHEET), 2);
+
+		assert_eq!(registry.release(&SHEET), None);
+		assert_eq!(registry.release(&SHEET), Some("style"));
+		assert_eq!(registry.count(&SHEET), 0);
+	}
+}
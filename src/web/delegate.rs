@@ -0,0 +1,267 @@
+//! Event delegation.
+//!
+//! In delegated mode (see [`Root::render_delegated`](super::root::Root::render_delegated))
+//! event listeners don't create a JS closure per element. Instead, a single listener
+//! per event type is added to the root container, and the event is dispatched to the
+//! handlers of `event.target` and its ancestors.
+//!
+//! Handlers should use `event.target()` rather than `event.current_target()`,
+//! the latter is always the container. `stopPropagation` stops at the node whose
+//! handlers are running, `stopImmediatePropagation` also skips its remaining handlers.
+//!
+//! Handlers are kept by the [`Delegation`] rather than in the [`Tree`] data of their
+//! elements. The dispatch starts from a DOM node, which would need the same
+//! `__skimaId` lookup to find its tree, and the delegation doesn't keep trees alive.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Element, Event, Node};
+
+use super::WebSys;
+use crate::tree::Tree;
+
+const NODE_ID: &str = "__skimaId";
+
+// Node ids are shared by all delegated roots, a portal may be nested in another root
+static NEXT_NODE_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Events that don't bubble. They are listened in the capture phase
+/// and dispatched to the target only.
+const NON_BUBBLING: &[&str] = &[
+	"focus",
+	"blur",
+	"load",
+	"unload",
+	"error",
+	"abort",
+	"scroll",
+	"mouseenter",
+	"mouseleave",
+	"pointerenter",
+	"pointerleave",
+	"invalid",
+	"play",
+	"pause",
+	"ended",
+];
+
+type Handler = Rc<dyn Fn(Event)>;
+
+pub struct Delegation {
+	container: Element,
+	next_id: Cell<u32>,
+	listeners: RefCell<HashMap<&'static str, Closure<dyn Fn(Event)>>>,
+	handlers: RefCell<HashMap<(u32, &'static str), Vec<(u32, Handler)>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HandlerId {
+	node: u32,
+	event: &'static str,
+	id: u32,
+}
+
+impl Delegation {
+	pub fn new(container: Element) -> Rc<Self> {
+		Rc::new(Delegation {
+			container,
+			next_id: Cell::new(1),
+			listeners: Default::default(),
+			handlers: Default::default(),
+		})
+	}
+
+	/// Delegation of the closest delegated root, if any
+	pub fn find(tree: &Tree<WebSys>) -> Option<Rc<Delegation>> {
+		let mut cursor = Some(tree.clone());
+		while let Some(tree) = cursor {
+			if let Some(delegation) = tree.data().try_get::<Rc<Delegation>>() {
+				return Some(delegation);
+			}
+			cursor = tree.parent.clone();
+		}
+
+		None
+	}
+
+	fn next_id(&self) -> u32 {
+		let id = self.next_id.get();
+		self.next_id.set(id + 1);
+		id
+	}
+
+	fn node_id(element: &Element) -> u32 {
+		let key = JsValue::from_str(NODE_ID);
+		if let Some(id) = js_sys::Reflect::get(element, &key)
+			.ok()
+			.and_then(|id| id.as_f64())
+		{
+			return id as u32;
+		}
+
+		let id = NEXT_NODE_ID.fetch_add(1, Ordering::Relaxed);
+		js_sys::Reflect::set(element, &key, &JsValue::from(id)).unwrap();
+		id
+	}
+
+	pub fn register(
+		self: &Rc<Self>,
+		element: &Element,
+		event: &'static str,
+		handler: Handler,
+	) -> HandlerId {
+		self.listen(event);
+
+		let node = Self::node_id(element);
+		let id = self.next_id();
+		self.handlers
+			.borrow_mut()
+			.entry((node, event))
+			.or_default()
+			.push((id, handler));
+
+		HandlerId { node, event, id }
+	}
+
+	pub fn unregister(&self, id: HandlerId) {
+		let mut handlers = self.handlers.borrow_mut();
+		if let Some(list) = handlers.get_mut(&(id.node, id.event)) {
+			list.retain(|(handler_id, _)| *handler_id != id.id);
+			if list.is_empty() {
+				handlers.remove(&(id.node, id.event));
+			}
+		}
+	}
+
+	// Adds the container listener for the event type once
+	fn listen(self: &Rc<Self>, event: &'static str) {
+		if self.listeners.borrow().contains_key(event) {
+			return;
+		}
+
+		let this = Rc::downgrade(self);
+		let closure = Closure::wrap(Box::new(move |e: Event| {
+			if let Some(this) = Weak::upgrade(&this) {
+				this.dispatch(event, e)
+			}
+		}) as Box<dyn Fn(Event)>);
+
+		self.container
+			.add_event_listener_with_callback_and_bool(
+				event,
+				closure.as_ref().unchecked_ref(),
+				NON_BUBBLING.contains(&event),
+			)
+			.unwrap();
+
+		self.listeners.borrow_mut().insert(event, closure);
+	}
+
+	fn dispatch(&self, event: &'static str, e: Event) {
+		let bubbles = !NON_BUBBLING.contains(&event);
+		let key = JsValue::from_str(NODE_ID);
+		let stopped = ImmediateStop::watch(&e);
+
+		let mut cursor = e.target().and_then(|t| t.dyn_into::<Node>().ok());
+		while let Some(node) = cursor {
+			if node.is_same_node(Some(&self.container)) {
+				break;
+			}
+
+			let id = js_sys::Reflect::get(&node, &key)
+				.ok()
+				.and_then(|id| id.as_f64());
+
+			if let Some(id) = id {
+				// Handlers may add or remove other handlers, so we don't hold the borrow
+				let handlers: Vec<Handler> = self
+					.handlers
+					.borrow()
+					.get(&(id as u32, event))
+					.map(|list| list.iter().map(|(_, h)| h.clone()).collect())
+					.unwrap_or_default();
+
+				for handler in handlers {
+					handler(e.clone());
+					if stopped.get() {
+						return;
+					}
+				}
+
+				// Other handlers of the same node still run after `stopPropagation`
+				if e.cancel_bubble() {
+					break;
+				}
+			}
+
+			if !bubbles {
+				break;
+			}
+
+			cursor = node.parent_node();
+		}
+	}
+}
+
+const STOP_IMMEDIATE: &str = "stopImmediatePropagation";
+
+/// Replaces `stopImmediatePropagation` of the event while it's dispatched,
+/// the DOM doesn't tell if it was called
+struct ImmediateStop {
+	event: Event,
+	stopped: Rc<Cell<bool>>,
+	_closure: Closure<dyn Fn()>,
+}
+
+impl ImmediateStop {
+	fn watch(event: &Event) -> Self {
+		let key = JsValue::from_str(STOP_IMMEDIATE);
+		let native: js_sys::Function = js_sys::Reflect::get(event, &key).unwrap().unchecked_into();
+		let stopped = Rc::new(Cell::new(false));
+
+		let closure = Closure::wrap(Box::new({
+			let event = event.clone();
+			let stopped = stopped.clone();
+			move || {
+				stopped.set(true);
+				let _ = native.call0(&event);
+			}
+		}) as Box<dyn Fn()>);
+
+		js_sys::Reflect::set(event, &key, closure.as_ref()).unwrap();
+
+		ImmediateStop {
+			event: event.clone(),
+			stopped,
+			_closure: closure,
+		}
+	}
+
+	fn get(&self) -> bool {
+		self.stopped.get()
+	}
+}
+
+impl Drop for ImmediateStop {
+	fn drop(&mut self) {
+		// The native method of the prototype is visible again
+		let _ = js_sys::Reflect::delete_property(&self.event, &JsValue::from_str(STOP_IMMEDIATE));
+	}
+}
+
+impl Drop for Delegation {
+	fn drop(&mut self) {
+		for (event, closure) in self.listeners.borrow_mut().drain() {
+			let _ = self.container.remove_event_listener_with_callback_and_bool(
+				event,
+				closure.as_ref().unchecked_ref(),
+				NON_BUBBLING.contains(&event),
+			);
+		}
+	}
+}
@@ -1,9 +1,12 @@
-use std::any::TypeId;
+use std::cell::RefMut;
 use std::marker::PhantomData;
+use std::panic::Location;
 
 use downcast_rs::{impl_downcast, Downcast};
 
-use super::context::{Extension, StatefulContext, WithEffects};
+use super::context::{
+	call_site_key, explicit_key, report_duplicate_key, Extension, StatefulContext, WithEffects,
+};
 use crate::Backend;
 
 pub struct EffectWithCleanup<F, C, D, B, E>
@@ -91,6 +94,7 @@ where
 	B: 'static,
 	E: 'static,
 {
+	#[track_caller]
 	pub fn effect<F>(&self, func: F)
 	where
 		F: FnOnce(&Self) + 'static,
@@ -102,15 +106,14 @@ where
 			_e: PhantomData,
 		};
 
-		let type_id = TypeId::of::<F>();
-		let effects = self.ext.get();
-		let mut effects = effects.effects.borrow_mut();
-		let effect_ctx = effects.entry(type_id).or_insert(Default::default());
+		let key = call_site_key::<F>();
+		let mut effect_ctx = self.ext.get().slot(key, Location::caller());
 
 		effect_ctx.scheduled = Some(Box::new(effect));
 		effect_ctx.alive = true;
 	}
 
+	#[track_caller]
 	pub fn effect_clean<F, C>(&self, func: F)
 	where
 		F: Fn(&Self) -> C + 'static,
@@ -124,52 +127,24 @@ where
 			_e: PhantomData,
 		};
 
-		let type_id = TypeId::of::<F>();
-		let effects = self.ext.get();
-		let mut effects = effects.effects.borrow_mut();
-		let effect_ctx = effects.entry(type_id).or_insert(Default::default());
+		let key = call_site_key::<F>();
+		let mut effect_ctx = self.ext.get().slot(key, Location::caller());
 
 		effect_ctx.scheduled = Some(Box::new(effect));
 		effect_ctx.alive = true;
 	}
 
+	#[track_caller]
 	pub fn effect_hash<F, D>(&self, deps: D, func: F)
 	where
 		F: Fn(&Self) + 'static,
 		D: std::hash::Hash,
 	{
 		let hash = fxhash::hash64(&deps);
-		self.effect_hash_internal(hash, func)
-	}
-
-	fn effect_hash_internal<F>(&self, hash: u64, func: F)
-	where
-		F: Fn(&Self) + 'static,
-	{
-		let effect = Effect {
-			func: Some(func),
-			dep: hash,
-			_b: PhantomData,
-			_e: PhantomData,
-		};
-
-		let type_id = TypeId::of::<F>();
-		let effects = self.ext.get();
-		let mut effects = effects.effects.borrow_mut();
-		let effect_ctx = effects.entry(type_id).or_insert(Default::default());
-
-		effect_ctx.alive = true;
-
-		if let Some(prev) = &effect_ctx.current {
-			let prev = prev.downcast_ref::<Effect<F, u64, B, E>>().unwrap();
-			if prev.dep != hash {
-				effect_ctx.scheduled = Some(Box::new(effect));
-			}
-		} else {
-			effect_ctx.scheduled = Some(Box::new(effect));
-		}
+		self.effect_eq(hash, func)
 	}
 
+	#[track_caller]
 	pub fn effect_hash_clean<F, D, C>(&self, deps: D, func: F)
 	where
 		F: FnOnce(&Self) -> C + 'static,
@@ -177,44 +152,15 @@ where
 		D: std::hash::Hash,
 	{
 		let hash = fxhash::hash64(&deps);
-		self.effect_hash_clean_internal(hash, func)
-	}
-
-	fn effect_hash_clean_internal<F, C>(&self, hash: u64, func: F)
-	where
-		F: FnOnce(&Self) -> C + 'static,
-		C: FnOnce(&Self) + 'static,
-	{
-		let effect = EffectWithCleanup {
-			func: Some(func),
-			cleanup: None,
-			dep: hash,
-			_b: PhantomData,
-			_e: PhantomData,
-		};
-
-		let type_id = TypeId::of::<F>();
-		let effects = self.ext.get();
-		let mut effects = effects.effects.borrow_mut();
-		let effect_ctx = effects.entry(type_id).or_insert(Default::default());
-
-		effect_ctx.alive = true;
-		if let Some(prev) = &effect_ctx.current {
-			let prev = prev
-				.downcast_ref::<EffectWithCleanup<F, C, u64, B, E>>()
-				.unwrap();
-			if prev.dep != hash {
-				effect_ctx.scheduled = Some(Box::new(effect));
-			}
-		} else {
-			effect_ctx.scheduled = Some(Box::new(effect));
-		}
+		self.effect_eq_clean(hash, func)
 	}
 
+	#[track_caller]
 	pub fn effect_once(&self, func: impl FnOnce(&Self) + 'static) {
 		self.effect_eq(0, func)
 	}
 
+	#[track_caller]
 	pub fn effect_once_clean<F, C>(&self, func: F)
 	where
 		F: FnOnce(&Self) -> C + 'static,
@@ -223,14 +169,33 @@ where
 		self.effect_eq_clean(0, func)
 	}
 
+	#[track_caller]
 	pub fn effect_eq<F, D: PartialEq + 'static>(&self, deps: D, func: F)
 	where
 		F: FnOnce(&Self) + 'static,
 	{
-		let type_id = TypeId::of::<F>();
-		let effects = self.ext.get();
-		let mut effects = effects.effects.borrow_mut();
-		let effect_ctx = effects.entry(type_id).or_insert(Default::default());
+		let key = call_site_key::<F>();
+		self.effect_eq_internal(key, deps, func)
+	}
+
+	/// Same as [`StatefulContext::effect_eq`], but keyed explicitly instead of by the call site
+	#[track_caller]
+	pub fn effect_with_key<K, F, D>(&self, key: &K, deps: D, func: F)
+	where
+		K: std::hash::Hash + ?Sized,
+		F: FnOnce(&Self) + 'static,
+		D: PartialEq + 'static,
+	{
+		let key = explicit_key::<F, K>(key);
+		self.effect_eq_internal(key, deps, func)
+	}
+
+	#[track_caller]
+	fn effect_eq_internal<F, D: PartialEq + 'static>(&self, key: u64, deps: D, func: F)
+	where
+		F: FnOnce(&Self) + 'static,
+	{
+		let mut effect_ctx = self.ext.get().slot(key, Location::caller());
 
 		effect_ctx.alive = true;
 		if let Some(prev) = &effect_ctx.current {
@@ -255,16 +220,38 @@ where
 		}
 	}
 
+	#[track_caller]
 	pub fn effect_eq_clean<F, D, C>(&self, deps: D, func: F)
 	where
 		F: FnOnce(&Self) -> C + 'static,
 		C: FnOnce(&Self) + 'static,
 		D: PartialEq + 'static,
 	{
-		let type_id = TypeId::of::<F>();
-		let effects = self.ext.get();
-		let mut effects = effects.effects.borrow_mut();
-		let effect_ctx = effects.entry(type_id).or_insert(Default::default());
+		let key = call_site_key::<F>();
+		self.effect_eq_clean_internal(key, deps, func)
+	}
+
+	/// Same as [`StatefulContext::effect_eq_clean`], but keyed explicitly instead of by the call site
+	#[track_caller]
+	pub fn effect_clean_with_key<K, F, D, C>(&self, key: &K, deps: D, func: F)
+	where
+		K: std::hash::Hash + ?Sized,
+		F: FnOnce(&Self) -> C + 'static,
+		C: FnOnce(&Self) + 'static,
+		D: PartialEq + 'static,
+	{
+		let key = explicit_key::<F, K>(key);
+		self.effect_eq_clean_internal(key, deps, func)
+	}
+
+	#[track_caller]
+	fn effect_eq_clean_internal<F, D, C>(&self, key: u64, deps: D, func: F)
+	where
+		F: FnOnce(&Self) -> C + 'static,
+		C: FnOnce(&Self) + 'static,
+		D: PartialEq + 'static,
+	{
+		let mut effect_ctx = self.ext.get().slot(key, Location::caller());
 
 		effect_ctx.alive = true;
 		if let Some(prev) = &effect_ctx.current {
@@ -295,6 +282,19 @@ where
 }
 
 impl<B: Backend + 'static, E: 'static> WithEffects<B, E> {
+	/// Slot of an effect, reports effects that share a key within one render
+	fn slot(&self, key: u64, location: &Location) -> RefMut<EffectContext<B, E>> {
+		let effects = self.effects.borrow_mut();
+		let effect = RefMut::map(effects, |effects| effects.entry(key).or_default());
+
+		// `alive` is reset before every render
+		if cfg!(debug_assertions) && effect.alive {
+			report_duplicate_key(location);
+		}
+
+		effect
+	}
+
 	pub(crate) fn reset_effects_alive(&self) {
 		for effect in self.effects.borrow_mut().values_mut() {
 			effect.alive = false;
@@ -303,23 +303,26 @@ impl<B: Backend + 'static, E: 'static> WithEffects<B, E> {
 
 	/// Run all scheduled effects
 	pub(crate) fn run_effects(&self, context: &StatefulContext<B, E>) {
-		for effect in self.effects.borrow_mut().values_mut() {
+		self.effects.borrow_mut().retain(|_, effect| {
+			// Not called during the last render, e.g. its key is gone
 			if !effect.alive {
-				assert!(effect.current.is_some());
 				assert!(effect.scheduled.is_none());
-				let mut current = effect.current.take().unwrap();
-				current.cleanup(context);
-				continue;
+				if let Some(mut current) = effect.current.take() {
+					current.cleanup(context);
+				}
+				return false;
 			}
 
 			if let Some(mut scheduled) = effect.scheduled.take() {
-				if let Some(mut previous) = effect.scheduled.take() {
+				if let Some(mut previous) = effect.current.take() {
 					previous.cleanup(context)
 				}
 				scheduled.run(context);
 				effect.current = Some(scheduled);
 			}
-		}
+
+			true
+		});
 	}
 
 	pub(crate) fn cleanup_effects_internal(&self, context: &StatefulContext<B, E>) {
@@ -1,25 +1,30 @@
 use std::any::TypeId;
 use std::cell::RefCell;
-use std::rc::Rc;
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
 
 use wasm_bindgen::prelude::Closure;
 use wasm_bindgen::JsCast;
-use web_sys::HtmlElement;
+use web_sys::{AddEventListenerOptions, Element};
 
+use super::delegate::{Delegation, HandlerId};
+use super::global::ListenerGuard;
+use super::HtmlBackend;
 use crate::tree::Tree;
 use crate::web::{Callback, Markup, WebSys};
 
-pub trait EventCallback: Clone + 'static {
+/// Event handler, `E` is the event type of the backend, e.g. `web_sys::Event`
+pub trait EventCallback<E>: Clone + 'static {
 	fn type_id(&self) -> TypeId;
 	fn eq(&self, other: &Self) -> bool;
-	fn call(&self, event: web_sys::Event);
+	fn call(&self, event: E);
 }
 
-impl<T> EventCallback for Rc<T>
+impl<E, T> EventCallback<E> for Rc<T>
 where
-	T: Fn(web_sys::Event) + 'static,
+	T: Fn(E) + 'static,
 {
-	fn call(&self, event: web_sys::Event) {
+	fn call(&self, event: E) {
 		self(event)
 	}
 
@@ -32,40 +37,147 @@ where
 	}
 }
 
-pub struct EventListener<C>
+/// Converts the backend event into a concrete event type.
+/// Backends without a DOM never call typed handlers.
+pub trait CastEvent<E> {
+	fn cast(self) -> Option<E>;
+}
+
+impl<E: JsCast> CastEvent<E> for web_sys::Event {
+	fn cast(self) -> Option<E> {
+		Some(self.unchecked_into())
+	}
+}
+
+impl<E> CastEvent<E> for () {
+	fn cast(self) -> Option<E> {
+		None
+	}
+}
+
+/// Casts the event to `E` before calling the callback
+pub struct Typed<E, C> {
+	callback: C,
+	_e: PhantomData<fn(E)>,
+}
+
+impl<E, C> Typed<E, C> {
+	pub fn new(callback: C) -> Self {
+		Typed {
+			callback,
+			_e: PhantomData,
+		}
+	}
+}
+
+impl<E, C: Clone> Clone for Typed<E, C> {
+	fn clone(&self) -> Self {
+		Typed {
+			callback: self.callback.clone(),
+			_e: PhantomData,
+		}
+	}
+}
+
+impl<Ev, E, C> EventCallback<Ev> for Typed<E, C>
 where
-	C: EventCallback,
+	Ev: CastEvent<E>,
+	E: 'static,
+	C: EventCallback<E>,
 {
+	fn type_id(&self) -> TypeId {
+		self.callback.type_id()
+	}
+
+	fn eq(&self, other: &Self) -> bool {
+		self.callback.eq(&other.callback)
+	}
+
+	fn call(&self, event: Ev) {
+		if let Some(event) = event.cast() {
+			self.callback.call(event)
+		}
+	}
+}
+
+pub struct EventListener<C> {
 	pub(crate) event: &'static str,
 	key: u64,
 	pub(crate) callback: C,
+	pub(crate) options: ListenerOptions,
 }
 
-impl<C> EventListener<C>
-where
-	C: EventCallback,
-{
-	pub fn new(event: &'static str, callback: C) -> Self {
+/// Maps onto `AddEventListenerOptions`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerOptions {
+	pub capture: bool,
+	pub passive: bool,
+	pub once: bool,
+}
+
+impl<C> EventListener<C> {
+	pub fn new<E>(event: &'static str, callback: C) -> Self
+	where
+		C: EventCallback<E>,
+	{
 		let key = fxhash::hash64(&callback.type_id());
 		EventListener {
 			event,
 			callback,
 			key,
+			options: ListenerOptions::default(),
 		}
 	}
+
+	/// Listen in the capture phase
+	pub fn capture(mut self) -> Self {
+		self.options.capture = true;
+		self
+	}
+
+	/// The handler never calls `prevent_default`, lets the browser scroll without waiting
+	pub fn passive(mut self) -> Self {
+		self.options.passive = true;
+		self
+	}
+
+	/// Remove the listener after the first event
+	pub fn once(mut self) -> Self {
+		self.options.once = true;
+		self
+	}
+
+	pub fn with_options(mut self, options: ListenerOptions) -> Self {
+		self.options = options;
+		self
+	}
 }
 
-struct EventListenerData<C>
-where
-	C: EventCallback,
-{
-	func: RefCell<C>,
-	closure: Closure<dyn Fn(web_sys::Event)>,
+pub(crate) struct EventListenerData<C, L> {
+	pub(crate) func: RefCell<C>,
+	pub(crate) listener: RefCell<Option<L>>,
+}
+
+// Not a closure inside `render`, so that the handler doesn't capture the backend lifetime
+pub(crate) fn handler<E: 'static, C: EventCallback<E>, L: 'static>(
+	data: &Rc<EventListenerData<C, L>>,
+) -> Rc<dyn Fn(E)> {
+	let data = Rc::downgrade(data);
+	Rc::new(move |event| {
+		if let Some(data) = Weak::upgrade(&data) {
+			// we clone here to allow `func` to modify the callback
+			// by triggering a state update
+			let func = { data.func.borrow().clone() };
+			func.call(event)
+		}
+	})
 }
 
-impl<C> Markup<WebSys> for EventListener<C>
+impl<B, C> Markup<B> for EventListener<C>
 where
-	C: EventCallback,
+	B: HtmlBackend,
+	B::Event: 'static,
+	C: EventCallback<B::Event>,
 {
 	fn has_own_node() -> bool {
 		false
@@ -75,83 +187,161 @@ where
 		true
 	}
 
-	fn render(&mut self, tree: &Tree<WebSys>) {
+	fn render(&mut self, tree: &Tree<B>) {
 		tracing::debug!("Rendering event {}", self.event);
 
-		let data = Rc::<EventListenerData<C>>::new_cyclic(|this| {
-			let data = this.clone();
-			let closure = Closure::wrap(Box::new(move |event| {
-				// we clone here to allow `func` to modify the callback
-				// by triggering a state update
-				let func = { data.upgrade().unwrap().func.borrow().clone() };
-				(func).call(event)
-			}) as Box<dyn Fn(web_sys::Event)>);
-
-			EventListenerData {
-				func: RefCell::new(self.callback.clone()),
-				closure,
-			}
+		let data = Rc::new(EventListenerData::<C, B::Listener> {
+			func: RefCell::new(self.callback.clone()),
+			listener: RefCell::new(None),
 		});
 
-		tree.closest_node()
-			.unchecked_ref::<HtmlElement>()
-			.add_event_listener_with_callback(self.event, data.closure.as_ref().unchecked_ref())
-			.unwrap();
+		let listener = tree
+			.backend
+			.add_listener(tree, self.event, self.options, handler(&data));
+		data.listener.replace(Some(listener));
 
 		tree.data_mut().set_with_key(self.key, data);
 	}
 
-	fn diff(&mut self, _prev: &mut Self, tree: &Tree<WebSys>) {
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		if self.event != prev.event || self.options != prev.options {
+			prev.drop(tree, false);
+			self.render(tree);
+			return;
+		}
+
 		let data = tree
 			.data_mut()
-			.get_with_key::<Rc<EventListenerData<C>>>(self.key);
-		if !self.callback.eq(&_prev.callback) {
+			.get_with_key::<Rc<EventListenerData<C, B::Listener>>>(self.key);
+		if !self.callback.eq(&prev.callback) {
 			*data.func.borrow_mut() = self.callback.clone();
 		}
 	}
 
-	fn drop(&mut self, tree: &Tree<WebSys>, _should_unmount: bool) {
+	fn drop(&mut self, tree: &Tree<B>, _should_unmount: bool) {
 		tracing::info!("Drop event {}", self.event);
 		let data = tree
 			.data_mut()
-			.remove_with_key::<Rc<EventListenerData<C>>>(self.key);
+			.remove_with_key::<Rc<EventListenerData<C, B::Listener>>>(self.key);
 
-		tree.closest_node()
-			.unchecked_ref::<HtmlElement>()
-			.remove_event_listener_with_callback(self.event, data.closure.as_ref().unchecked_ref())
-			.unwrap()
+		if let Some(listener) = data.listener.take() {
+			tree.backend.remove_listener(tree, listener);
+		}
 	}
 }
 
-pub trait IntoEventCallback {
-	type Callback: EventCallback;
+/// Event listener of the [`WebSys`] backend
+pub enum WebListener {
+	Direct {
+		event: &'static str,
+		capture: bool,
+		closure: Closure<dyn Fn(web_sys::Event)>,
+	},
+	Delegated(Rc<Delegation>, HandlerId),
+	/// On `window` or `document`, removed when dropped
+	Global(ListenerGuard),
+}
+
+impl WebListener {
+	pub(crate) fn add(
+		tree: &Tree<WebSys>,
+		event: &'static str,
+		options: ListenerOptions,
+		handler: Rc<dyn Fn(web_sys::Event)>,
+	) -> Self {
+		let element = tree.closest_node().unchecked_into::<Element>();
+
+		// Delegation only supports plain bubbling listeners
+		if options == ListenerOptions::default() {
+			if let Some(delegation) = Delegation::find(tree) {
+				let id = delegation.register(&element, event, handler);
+				return WebListener::Delegated(delegation, id);
+			}
+		}
+
+		let closure = Closure::wrap(Box::new(move |e| handler(e)) as Box<dyn Fn(web_sys::Event)>);
+
+		let js_options = AddEventListenerOptions::new();
+		js_options.set_capture(options.capture);
+		js_options.set_passive(options.passive);
+		js_options.set_once(options.once);
+
+		element
+			.add_event_listener_with_callback_and_add_event_listener_options(
+				event,
+				closure.as_ref().unchecked_ref(),
+				&js_options,
+			)
+			.unwrap();
+
+		WebListener::Direct {
+			event,
+			capture: options.capture,
+			closure,
+		}
+	}
+
+	pub(crate) fn remove(self, tree: &Tree<WebSys>) {
+		match self {
+			WebListener::Direct {
+				event,
+				capture,
+				closure,
+			} => tree
+				.closest_node()
+				.remove_event_listener_with_callback_and_bool(
+					event,
+					closure.as_ref().unchecked_ref(),
+					capture,
+				)
+				.unwrap(),
+			WebListener::Delegated(delegation, id) => delegation.unregister(id),
+			WebListener::Global(guard) => std::mem::drop(guard),
+		}
+	}
+}
+
+/// Conversion into an [`EventCallback`], `E` is the event type the callback accepts
+///
+/// The callback is not bound to `E` here, so the same conversion also serves typed listeners
+/// that receive a plain `web_sys::Event` and cast it, see [`Typed`].
+pub trait IntoEventCallback<E> {
+	type Callback;
 	fn into_callback(self) -> Self::Callback;
 }
 
-impl<F> IntoEventCallback for F
+impl<E, F> IntoEventCallback<E> for F
 where
-	F: Fn(web_sys::Event) + 'static,
+	E: 'static,
+	F: Fn(E) + 'static,
 {
-	type Callback = Rc<Self>;
+	type Callback = Typed<E, Rc<Self>>;
 	fn into_callback(self) -> Self::Callback {
-		Rc::new(self)
+		Typed::new(Rc::new(self))
 	}
 }
 
-impl IntoEventCallback for Callback<dyn Fn(web_sys::Event)> {
-	type Callback = Self;
+impl<E: 'static> IntoEventCallback<E> for Callback<dyn Fn(E)> {
+	type Callback = Typed<E, Self>;
 	fn into_callback(self) -> Self::Callback {
-		self
+		Typed::new(self)
 	}
 }
 
-impl IntoEventCallback for Callback<dyn Fn()> {
+// Works with any event type, `()` only guides the inference
+impl IntoEventCallback<()> for Callback<dyn Fn()> {
 	type Callback = Self;
 	fn into_callback(self) -> Self::Callback {
 		self
 	}
 }
 
-pub fn on<C: IntoEventCallback>(event: &'static str, callback: C) -> EventListener<C::Callback> {
+pub fn on<E, C: IntoEventCallback<E>>(
+	event: &'static str,
+	callback: C,
+) -> EventListener<C::Callback>
+where
+	C::Callback: EventCallback<E>,
+{
 	EventListener::new(event, callback.into_callback())
 }
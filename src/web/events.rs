@@ -0,0 +1,137 @@
+//! Typed event handlers.
+//!
+//! Each event name is tied to its `web_sys` event type, so the handler
+//! receives e.g. a [`web_sys::KeyboardEvent`] instead of a plain [`web_sys::Event`]:
+//!
+//! ```ignore
+//! input(on_keydown(|ev: KeyboardEvent| console_log!("{}", ev.key())))
+//! ```
+
+use wasm_bindgen::JsCast;
+
+use super::event::{EventCallback, EventListener, IntoEventCallback};
+
+/// Event name and the type of the event object
+pub trait EventType: 'static {
+	const NAME: &'static str;
+	type Event: JsCast + 'static;
+}
+
+pub fn on_event<T, C>(callback: C) -> EventListener<C::Callback>
+where
+	T: EventType,
+	C: IntoEventCallback<T::Event>,
+	C::Callback: EventCallback<web_sys::Event>,
+{
+	EventListener::new::<web_sys::Event>(T::NAME, callback.into_callback())
+}
+
+macro_rules! events {
+	($($kind:ident, $func:ident, $name:literal, $event:ident;)*) => {
+		/// Event types for [`on_event`]
+		pub mod kind {
+			use super::EventType;
+
+			$(
+				pub struct $kind;
+
+				impl EventType for $kind {
+					const NAME: &'static str = $name;
+					type Event = web_sys::$event;
+				}
+			)*
+		}
+
+		$(
+			pub fn $func<C>(callback: C) -> EventListener<C::Callback>
+			where
+				C: IntoEventCallback<web_sys::$event>,
+				C::Callback: EventCallback<web_sys::Event>,
+			{
+				on_event::<kind::$kind, C>(callback)
+			}
+		)*
+
+		/// Only the `on_*` helpers, re-exported by the prelude
+		pub mod handlers {
+			pub use super::{on_event, $($func),*};
+		}
+	};
+}
+
+events! {
+	Click, on_click, "click", MouseEvent;
+	AuxClick, on_auxclick, "auxclick", MouseEvent;
+	DblClick, on_dblclick, "dblclick", MouseEvent;
+	ContextMenu, on_contextmenu, "contextmenu", MouseEvent;
+	MouseDown, on_mousedown, "mousedown", MouseEvent;
+	MouseUp, on_mouseup, "mouseup", MouseEvent;
+	MouseMove, on_mousemove, "mousemove", MouseEvent;
+	MouseOver, on_mouseover, "mouseover", MouseEvent;
+	MouseOut, on_mouseout, "mouseout", MouseEvent;
+	MouseEnter, on_mouseenter, "mouseenter", MouseEvent;
+	MouseLeave, on_mouseleave, "mouseleave", MouseEvent;
+	Wheel, on_wheel, "wheel", WheelEvent;
+
+	KeyDown, on_keydown, "keydown", KeyboardEvent;
+	KeyUp, on_keyup, "keyup", KeyboardEvent;
+
+	// Not an `InputEvent` for every kind of input, e.g. checkboxes
+	Input, on_input, "input", Event;
+	BeforeInput, on_beforeinput, "beforeinput", InputEvent;
+	Change, on_change, "change", Event;
+	Select, on_select, "select", Event;
+	Invalid, on_invalid, "invalid", Event;
+	Submit, on_submit, "submit", SubmitEvent;
+	Reset, on_reset, "reset", Event;
+
+	Focus, on_focus, "focus", FocusEvent;
+	Blur, on_blur, "blur", FocusEvent;
+	FocusIn, on_focusin, "focusin", FocusEvent;
+	FocusOut, on_focusout, "focusout", FocusEvent;
+
+	PointerDown, on_pointerdown, "pointerdown", PointerEvent;
+	PointerUp, on_pointerup, "pointerup", PointerEvent;
+	PointerMove, on_pointermove, "pointermove", PointerEvent;
+	PointerOver, on_pointerover, "pointerover", PointerEvent;
+	PointerOut, on_pointerout, "pointerout", PointerEvent;
+	PointerEnter, on_pointerenter, "pointerenter", PointerEvent;
+	PointerLeave, on_pointerleave, "pointerleave", PointerEvent;
+	PointerCancel, on_pointercancel, "pointercancel", PointerEvent;
+	GotPointerCapture, on_gotpointercapture, "gotpointercapture", PointerEvent;
+	LostPointerCapture, on_lostpointercapture, "lostpointercapture", PointerEvent;
+
+	TouchStart, on_touchstart, "touchstart", TouchEvent;
+	TouchEnd, on_touchend, "touchend", TouchEvent;
+	TouchMove, on_touchmove, "touchmove", TouchEvent;
+	TouchCancel, on_touchcancel, "touchcancel", TouchEvent;
+
+	Drag, on_drag, "drag", DragEvent;
+	DragStart, on_dragstart, "dragstart", DragEvent;
+	DragEnd, on_dragend, "dragend", DragEvent;
+	DragEnter, on_dragenter, "dragenter", DragEvent;
+	DragLeave, on_dragleave, "dragleave", DragEvent;
+	DragOver, on_dragover, "dragover", DragEvent;
+	DropEvent, on_drop, "drop", DragEvent;
+
+	CopyEvent, on_copy, "copy", ClipboardEvent;
+	Cut, on_cut, "cut", ClipboardEvent;
+	Paste, on_paste, "paste", ClipboardEvent;
+
+	CompositionStart, on_compositionstart, "compositionstart", CompositionEvent;
+	CompositionUpdate, on_compositionupdate, "compositionupdate", CompositionEvent;
+	CompositionEnd, on_compositionend, "compositionend", CompositionEvent;
+
+	AnimationStart, on_animationstart, "animationstart", AnimationEvent;
+	AnimationEnd, on_animationend, "animationend", AnimationEvent;
+	AnimationIteration, on_animationiteration, "animationiteration", AnimationEvent;
+	TransitionStart, on_transitionstart, "transitionstart", TransitionEvent;
+	TransitionEnd, on_transitionend, "transitionend", TransitionEvent;
+	TransitionRun, on_transitionrun, "transitionrun", TransitionEvent;
+	TransitionCancel, on_transitioncancel, "transitioncancel", TransitionEvent;
+
+	Scroll, on_scroll, "scroll", Event;
+	Load, on_load, "load", Event;
+	ErrorEvent, on_error, "error", Event;
+	Toggle, on_toggle, "toggle", Event;
+}
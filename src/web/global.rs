@@ -0,0 +1,190 @@
+//! Event listeners on `window` and `document`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{AddEventListenerOptions, EventTarget};
+
+use super::event::{handler, EventCallback, EventListenerData, IntoEventCallback, ListenerOptions};
+use super::helpers::dom::{DOCUMENT, WINDOW};
+use super::HtmlBackend;
+use crate::tree::Tree;
+use crate::Markup;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlobalTarget {
+	Window,
+	Document,
+}
+
+/// Removes the listener when dropped
+pub struct ListenerGuard {
+	target: EventTarget,
+	event: &'static str,
+	capture: bool,
+	closure: Closure<dyn Fn(web_sys::Event)>,
+}
+
+impl ListenerGuard {
+	pub fn new(
+		target: GlobalTarget,
+		event: &'static str,
+		options: ListenerOptions,
+		handler: Rc<dyn Fn(web_sys::Event)>,
+	) -> Self {
+		let target: EventTarget = match target {
+			GlobalTarget::Window => WINDOW.with(|w| w.clone().unchecked_into()),
+			GlobalTarget::Document => DOCUMENT.with(|d| d.clone().unchecked_into()),
+		};
+
+		let closure = Closure::wrap(Box::new(move |e| handler(e)) as Box<dyn Fn(web_sys::Event)>);
+
+		let js_options = AddEventListenerOptions::new();
+		js_options.set_capture(options.capture);
+		js_options.set_passive(options.passive);
+		js_options.set_once(options.once);
+
+		target
+			.add_event_listener_with_callback_and_add_event_listener_options(
+				event,
+				closure.as_ref().unchecked_ref(),
+				&js_options,
+			)
+			.unwrap();
+
+		ListenerGuard {
+			target,
+			event,
+			capture: options.capture,
+			closure,
+		}
+	}
+}
+
+impl Drop for ListenerGuard {
+	fn drop(&mut self) {
+		let _ = self.target.remove_event_listener_with_callback_and_bool(
+			self.event,
+			self.closure.as_ref().unchecked_ref(),
+			self.capture,
+		);
+	}
+}
+
+pub struct GlobalListener<C> {
+	target: GlobalTarget,
+	event: &'static str,
+	key: u64,
+	callback: C,
+	options: ListenerOptions,
+}
+
+impl<C> GlobalListener<C> {
+	pub fn new<E>(target: GlobalTarget, event: &'static str, callback: C) -> Self
+	where
+		C: EventCallback<E>,
+	{
+		let key = fxhash::hash64(&(target, event, callback.type_id()));
+		GlobalListener {
+			target,
+			event,
+			key,
+			callback,
+			options: ListenerOptions::default(),
+		}
+	}
+
+	pub fn capture(mut self) -> Self {
+		self.options.capture = true;
+		self
+	}
+
+	pub fn passive(mut self) -> Self {
+		self.options.passive = true;
+		self
+	}
+
+	pub fn once(mut self) -> Self {
+		self.options.once = true;
+		self
+	}
+}
+
+impl<B, C> Markup<B> for GlobalListener<C>
+where
+	B: HtmlBackend,
+	B::Event: 'static,
+	C: EventCallback<B::Event>,
+{
+	fn has_own_node() -> bool {
+		false
+	}
+
+	fn dynamic() -> bool {
+		true
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		let data = Rc::new(EventListenerData::<C, B::Listener> {
+			func: RefCell::new(self.callback.clone()),
+			listener: RefCell::new(None),
+		});
+
+		// `None` on backends without `window` and `document`
+		let listener =
+			tree.backend
+				.listen_global(self.target, self.event, self.options, handler(&data));
+		data.listener.replace(listener);
+
+		tree.data_mut().set_with_key(self.key, data);
+	}
+
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		if self.key != prev.key || self.options != prev.options {
+			prev.drop(tree, false);
+			self.render(tree);
+			return;
+		}
+
+		if !self.callback.eq(&prev.callback) {
+			let data = tree
+				.data()
+				.get_with_key::<Rc<EventListenerData<C, B::Listener>>>(self.key);
+			*data.func.borrow_mut() = self.callback.clone();
+		}
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, _should_unmount: bool) {
+		let data = tree
+			.data_mut()
+			.remove_with_key::<Rc<EventListenerData<C, B::Listener>>>(self.key);
+
+		if let Some(listener) = data.listener.take() {
+			tree.backend.unlisten_global(self.target, listener);
+		}
+	}
+}
+
+/// Listens to `window` events while mounted, e.g. `resize` or `popstate`
+pub fn on_window<E, C: IntoEventCallback<E>>(
+	event: &'static str,
+	callback: C,
+) -> GlobalListener<C::Callback>
+where
+	C::Callback: EventCallback<E>,
+{
+	GlobalListener::new(GlobalTarget::Window, event, callback.into_callback())
+}
+
+/// Listens to `document` events while mounted, e.g. `visibilitychange`
+pub fn on_document<E, C: IntoEventCallback<E>>(
+	event: &'static str,
+	callback: C,
+) -> GlobalListener<C::Callback>
+where
+	C::Callback: EventCallback<E>,
+{
+	GlobalListener::new(GlobalTarget::Document, event, callback.into_callback())
+}
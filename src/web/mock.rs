@@ -0,0 +1,907 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Write};
+use std::rc::{Rc, Weak};
+
+use wasm_bindgen::JsCast;
+
+use super::css::{StyleRegistry, Stylesheet};
+use super::event::{CastEvent, ListenerOptions};
+use super::global::GlobalTarget;
+use super::prop::PropValue;
+use super::HtmlBackend;
+use crate::hydrate::{hydrate, HydrationMismatch};
+use crate::tree::Tree;
+use crate::{render_subtree, subtree, Backend, Markup, Namespace};
+
+/// A single operation performed by the Markup tree against the [`MockDom`] backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+	CreateElement { id: usize, tag: &'static str },
+	CreateText { id: usize, data: String },
+	Insert { id: usize, parent: usize, index: usize },
+	Replace { id: usize, prev: usize },
+	Remove { id: usize },
+	Move { id: usize, parent: usize, index: usize },
+	SetText { id: usize, data: String },
+	SetAttribute { id: usize, name: String, value: String },
+	RemoveAttribute { id: usize, name: String },
+	SetProperty { id: usize, name: String, value: String },
+	RemoveProperty { id: usize, name: String },
+	AddClass { id: usize, class: String },
+	RemoveClass { id: usize, class: String },
+	SetDomProperty { id: usize, name: String, value: String },
+	AddListener { id: usize, event: &'static str },
+	RemoveListener { id: usize, event: &'static str },
+	MountStyle { class: String },
+	UnmountStyle { class: String },
+}
+
+#[derive(Debug, Default)]
+struct MockLog {
+	next_id: Cell<usize>,
+	mutations: RefCell<Vec<Mutation>>,
+	styles: RefCell<StyleRegistry<()>>,
+}
+
+impl MockLog {
+	fn next_id(&self) -> usize {
+		let id = self.next_id.get();
+		self.next_id.set(id + 1);
+		id
+	}
+
+	fn record(&self, mutation: Mutation) {
+		self.mutations.borrow_mut().push(mutation)
+	}
+}
+
+/// In-memory backend that keeps a real node tree and records every mutation.
+///
+/// Intended for native tests of `Markup::render`/`diff`/`drop`.
+#[derive(Debug, Clone, Default)]
+pub struct MockDom {
+	log: Rc<MockLog>,
+	globals: Rc<RefCell<HashMap<GlobalTarget, MockNode>>>,
+}
+
+impl MockDom {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Creates a detached element to be used as a root container.
+	/// Containers are not recorded in the mutation log.
+	pub fn container(&self) -> MockNode {
+		MockNode::new(
+			&self.log,
+			MockNodeKind::Element(MockElement::new(Namespace::Html, "root")),
+		)
+	}
+
+	pub fn mutations(&self) -> Vec<Mutation> {
+		self.log.mutations.borrow().clone()
+	}
+
+	pub fn take_mutations(&self) -> Vec<Mutation> {
+		std::mem::take(&mut *self.log.mutations.borrow_mut())
+	}
+
+	/// Detached element that stands for `window` or `document`, to dispatch global events
+	pub fn global(&self, target: GlobalTarget) -> MockNode {
+		self.globals
+			.borrow_mut()
+			.entry(target)
+			.or_insert_with(|| {
+				let tag = match target {
+					GlobalTarget::Window => "window",
+					GlobalTarget::Document => "document",
+				};
+				MockNode::new(
+					&self.log,
+					MockNodeKind::Element(MockElement::new(Namespace::Html, tag)),
+				)
+			})
+			.clone()
+	}
+
+	fn listen(
+		&self,
+		element: &MockNode,
+		event: &'static str,
+		options: ListenerOptions,
+		handler: Rc<dyn Fn(MockEvent)>,
+	) -> usize {
+		let id = self.log.next_id();
+		element.element().listeners.borrow_mut().push(MockListener {
+			id,
+			event,
+			capture: options.capture,
+			once: options.once,
+			handler,
+		});
+
+		self.log.record(Mutation::AddListener {
+			id: element.id(),
+			event,
+		});
+
+		id
+	}
+
+	fn unlisten(&self, element: &MockNode, listener: usize) {
+		let mut listeners = element.element().listeners.borrow_mut();
+
+		// `once` listeners are already gone after the first event
+		if let Some(index) = listeners.iter().position(|l| l.id == listener) {
+			let listener = listeners.remove(index);
+			self.log.record(Mutation::RemoveListener {
+				id: element.id(),
+				event: listener.event,
+			});
+		}
+	}
+}
+
+struct MockElement {
+	namespace: Namespace,
+	tag: &'static str,
+	attributes: RefCell<BTreeMap<String, String>>,
+	style: RefCell<BTreeMap<String, String>>,
+	properties: RefCell<BTreeMap<String, PropValue<'static>>>,
+	listeners: RefCell<Vec<MockListener>>,
+	children: RefCell<Vec<MockNode>>,
+}
+
+struct MockListener {
+	id: usize,
+	event: &'static str,
+	capture: bool,
+	once: bool,
+	handler: Rc<dyn Fn(MockEvent)>,
+}
+
+impl MockElement {
+	fn new(namespace: Namespace, tag: &'static str) -> Self {
+		MockElement {
+			namespace,
+			tag,
+			attributes: Default::default(),
+			style: Default::default(),
+			properties: Default::default(),
+			listeners: Default::default(),
+			children: Default::default(),
+		}
+	}
+}
+
+enum MockNodeKind {
+	Element(MockElement),
+	Text(RefCell<String>),
+}
+
+struct MockNodeInner {
+	id: usize,
+	kind: MockNodeKind,
+	parent: RefCell<Weak<MockNodeInner>>,
+	log: Rc<MockLog>,
+}
+
+#[derive(Clone)]
+pub struct MockNode(Rc<MockNodeInner>);
+
+impl PartialEq for MockNode {
+	fn eq(&self, other: &Self) -> bool {
+		Rc::ptr_eq(&self.0, &other.0)
+	}
+}
+
+impl Eq for MockNode {}
+
+impl Debug for MockNode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "#{} {}", self.0.id, self.to_html())
+	}
+}
+
+impl MockNode {
+	fn new(log: &Rc<MockLog>, kind: MockNodeKind) -> Self {
+		MockNode(Rc::new(MockNodeInner {
+			id: log.next_id(),
+			kind,
+			parent: RefCell::new(Weak::new()),
+			log: log.clone(),
+		}))
+	}
+
+	fn element(&self) -> &MockElement {
+		match &self.0.kind {
+			MockNodeKind::Element(element) => element,
+			MockNodeKind::Text(_) => panic!("Node #{} is not an element", self.0.id),
+		}
+	}
+
+	fn set_parent(&self, parent: Option<&MockNode>) {
+		self.0
+			.parent
+			.replace(parent.map(|p| Rc::downgrade(&p.0)).unwrap_or_default());
+	}
+
+	fn index_in(&self, parent: &MockNode) -> usize {
+		parent
+			.element()
+			.children
+			.borrow()
+			.iter()
+			.position(|child| child == self)
+			.expect("Node should be a child of its parent")
+	}
+
+	// Calls the listeners of one propagation phase
+	fn fire(&self, event: &MockEvent, capture: bool) {
+		let listeners: Vec<_> = self
+			.element()
+			.listeners
+			.borrow()
+			.iter()
+			.filter(|l| l.event == event.name() && l.capture == capture)
+			.map(|l| (l.id, l.once, l.handler.clone()))
+			.collect();
+
+		event.0.current_target.replace(Some(self.clone()));
+		for (id, once, handler) in listeners {
+			if once {
+				self.element().listeners.borrow_mut().retain(|l| l.id != id);
+			}
+
+			handler(event.clone());
+		}
+	}
+
+	pub fn id(&self) -> usize {
+		self.0.id
+	}
+
+	pub fn is_element(&self) -> bool {
+		matches!(self.0.kind, MockNodeKind::Element(_))
+	}
+
+	pub fn tag(&self) -> Option<&'static str> {
+		match &self.0.kind {
+			MockNodeKind::Element(element) => Some(element.tag),
+			MockNodeKind::Text(_) => None,
+		}
+	}
+
+	pub fn namespace(&self) -> Option<Namespace> {
+		match &self.0.kind {
+			MockNodeKind::Element(element) => Some(element.namespace),
+			MockNodeKind::Text(_) => None,
+		}
+	}
+
+	pub fn text(&self) -> Option<String> {
+		match &self.0.kind {
+			MockNodeKind::Element(_) => None,
+			MockNodeKind::Text(data) => Some(data.borrow().clone()),
+		}
+	}
+
+	pub fn attribute(&self, name: &str) -> Option<String> {
+		self.element().attributes.borrow().get(name).cloned()
+	}
+
+	pub fn style(&self, name: &str) -> Option<String> {
+		self.element().style.borrow().get(name).cloned()
+	}
+
+	pub fn property(&self, name: &str) -> Option<PropValue<'static>> {
+		self.element().properties.borrow().get(name).cloned()
+	}
+
+	/// Changes a property the way user input would, without recording a mutation
+	pub fn set_live_property(&self, name: &str, value: impl Into<PropValue<'static>>) {
+		self.element()
+			.properties
+			.borrow_mut()
+			.insert(name.to_owned(), value.into());
+	}
+
+	pub fn has_class(&self, class: &str) -> bool {
+		self.attribute("class")
+			.map(|c| c.split_ascii_whitespace().any(|c| c == class))
+			.unwrap_or(false)
+	}
+
+	pub fn parent(&self) -> Option<MockNode> {
+		self.0.parent.borrow().upgrade().map(MockNode)
+	}
+
+	pub fn children(&self) -> Vec<MockNode> {
+		match &self.0.kind {
+			MockNodeKind::Element(element) => element.children.borrow().clone(),
+			MockNodeKind::Text(_) => Vec::new(),
+		}
+	}
+
+	pub fn to_html(&self) -> String {
+		let mut out = String::new();
+		self.write_html(&mut out).unwrap();
+		out
+	}
+
+	pub fn inner_html(&self) -> String {
+		let mut out = String::new();
+		for child in self.children() {
+			child.write_html(&mut out).unwrap();
+		}
+		out
+	}
+
+	fn write_html(&self, out: &mut String) -> std::fmt::Result {
+		match &self.0.kind {
+			MockNodeKind::Text(data) => out.write_str(&data.borrow()),
+			MockNodeKind::Element(element) => {
+				write!(out, "<{}", element.tag)?;
+				for (name, value) in element.attributes.borrow().iter() {
+					write!(out, r#" {}="{}""#, name, value)?;
+				}
+
+				let style = element.style.borrow();
+				if !style.is_empty() {
+					write!(out, r#" style=""#)?;
+					for (name, value) in style.iter() {
+						write!(out, "{}:{};", name, value)?;
+					}
+					write!(out, "\"")?;
+				}
+
+				write!(out, ">")?;
+				for child in element.children.borrow().iter() {
+					child.write_html(out)?;
+				}
+				write!(out, "</{}>", element.tag)
+			}
+		}
+	}
+}
+
+/// Event of the [`MockDom`] backend, see [`HtmlBackend::dispatch_event`]
+#[derive(Clone)]
+pub struct MockEvent(Rc<MockEventInner>);
+
+struct MockEventInner {
+	name: String,
+	bubbles: bool,
+	target: RefCell<Option<MockNode>>,
+	current_target: RefCell<Option<MockNode>>,
+	stopped: Cell<bool>,
+	default_prevented: Cell<bool>,
+}
+
+impl MockEvent {
+	pub fn new(name: &str) -> Self {
+		Self::with_bubbles(name, true)
+	}
+
+	/// An event like `focus`, that only reaches the target
+	pub fn non_bubbling(name: &str) -> Self {
+		Self::with_bubbles(name, false)
+	}
+
+	fn with_bubbles(name: &str, bubbles: bool) -> Self {
+		MockEvent(Rc::new(MockEventInner {
+			name: name.to_owned(),
+			bubbles,
+			target: Default::default(),
+			current_target: Default::default(),
+			stopped: Default::default(),
+			default_prevented: Default::default(),
+		}))
+	}
+
+	pub fn name(&self) -> &str {
+		&self.0.name
+	}
+
+	pub fn target(&self) -> Option<MockNode> {
+		self.0.target.borrow().clone()
+	}
+
+	pub fn current_target(&self) -> Option<MockNode> {
+		self.0.current_target.borrow().clone()
+	}
+
+	pub fn stop_propagation(&self) {
+		self.0.stopped.set(true)
+	}
+
+	pub fn prevent_default(&self) {
+		self.0.default_prevented.set(true)
+	}
+
+	pub fn default_prevented(&self) -> bool {
+		self.0.default_prevented.get()
+	}
+}
+
+impl Debug for MockEvent {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("MockEvent")
+			.field("name", &self.0.name)
+			.field("target", &self.target().map(|t| t.id()))
+			.finish()
+	}
+}
+
+impl CastEvent<MockEvent> for MockEvent {
+	fn cast(self) -> Option<MockEvent> {
+		Some(self)
+	}
+}
+
+// Handlers that expect a DOM event are never called
+impl<E: JsCast> CastEvent<E> for MockEvent {
+	fn cast(self) -> Option<E> {
+		None
+	}
+}
+
+pub enum MockCursor {
+	After(MockNode),
+	BeginningOf(MockNode),
+}
+
+impl MockCursor {
+	fn position(self) -> (MockNode, usize) {
+		match self {
+			MockCursor::After(after) => {
+				let parent = after.parent().expect("Cursor node should have a parent");
+				let index = after.index_in(&parent) + 1;
+				(parent, index)
+			}
+			MockCursor::BeginningOf(parent) => (parent, 0),
+		}
+	}
+}
+
+impl Backend for MockDom {
+	type Element = MockNode;
+	type Text = MockNode;
+	type Node = MockNode;
+	type Cursor = MockCursor;
+	type Event = MockEvent;
+
+	fn replace(node: &Self::Node, prev: &Self::Node) {
+		let parent = prev.parent().expect("Replaced node should have a parent");
+		let index = prev.index_in(&parent);
+
+		node.set_parent(Some(&parent));
+		prev.set_parent(None);
+		parent.element().children.borrow_mut()[index] = node.clone();
+
+		node.0.log.record(Mutation::Replace {
+			id: node.id(),
+			prev: prev.id(),
+		});
+	}
+
+	fn insert(cursor: Self::Cursor, node: &Self::Node) {
+		let (parent, index) = cursor.position();
+
+		node.set_parent(Some(&parent));
+		parent
+			.element()
+			.children
+			.borrow_mut()
+			.insert(index, node.clone());
+
+		node.0.log.record(Mutation::Insert {
+			id: node.id(),
+			parent: parent.id(),
+			index,
+		});
+	}
+
+	fn move_node(cursor: Self::Cursor, node: &Self::Node) {
+		if let Some(parent) = node.parent() {
+			let index = node.index_in(&parent);
+			parent.element().children.borrow_mut().remove(index);
+		}
+
+		let (parent, index) = cursor.position();
+
+		node.set_parent(Some(&parent));
+		parent
+			.element()
+			.children
+			.borrow_mut()
+			.insert(index, node.clone());
+
+		node.0.log.record(Mutation::Move {
+			id: node.id(),
+			parent: parent.id(),
+			index,
+		});
+	}
+
+	fn remove(node: &Self::Node) {
+		if let Some(parent) = node.parent() {
+			let index = node.index_in(&parent);
+			parent.element().children.borrow_mut().remove(index);
+			node.set_parent(None);
+			node.0.log.record(Mutation::Remove { id: node.id() });
+		}
+	}
+
+	fn cursor_beginning_of(node: &Self::Element) -> Self::Cursor {
+		MockCursor::BeginningOf(node.clone())
+	}
+
+	fn cursor_after(node: &Self::Node) -> Self::Cursor {
+		MockCursor::After(node.clone())
+	}
+
+	fn create_element(&self, tag: &'static str) -> Self::Element {
+		self.create_element_ns(Namespace::Html, tag)
+	}
+
+	fn create_element_ns(&self, namespace: Namespace, tag: &'static str) -> Self::Element {
+		let node = MockNode::new(
+			&self.log,
+			MockNodeKind::Element(MockElement::new(namespace, tag)),
+		);
+		self.log.record(Mutation::CreateElement {
+			id: node.id(),
+			tag,
+		});
+		node
+	}
+
+	fn create_text(&self, data: &str) -> Self::Text {
+		let node = MockNode::new(
+			&self.log,
+			MockNodeKind::Text(RefCell::new(data.to_owned())),
+		);
+		self.log.record(Mutation::CreateText {
+			id: node.id(),
+			data: data.to_owned(),
+		});
+		node
+	}
+
+	fn set_text(&self, text: &Self::Text, data: &str) {
+		match &text.0.kind {
+			MockNodeKind::Text(text) => text.replace(data.to_owned()),
+			MockNodeKind::Element(_) => panic!("Node #{} is not a text", text.id()),
+		};
+
+		self.log.record(Mutation::SetText {
+			id: text.id(),
+			data: data.to_owned(),
+		});
+	}
+
+	fn text_to_node(text: Self::Text) -> Self::Node {
+		text
+	}
+
+	fn element_to_node(element: Self::Element) -> Self::Node {
+		element
+	}
+
+	fn node_to_element(node: Self::Node) -> Option<Self::Element> {
+		node.is_element().then_some(node)
+	}
+
+	fn node_to_text(node: Self::Node) -> Option<Self::Text> {
+		(!node.is_element()).then_some(node)
+	}
+
+	fn print_node(node: &Self::Node) {
+		println!("{:?}", node)
+	}
+
+	fn first_child(element: &Self::Element) -> Option<Self::Node> {
+		element.element().children.borrow().first().cloned()
+	}
+
+	fn next_sibling(node: &Self::Node) -> Option<Self::Node> {
+		let parent = node.parent()?;
+		let index = node.index_in(&parent);
+		let next = parent.element().children.borrow().get(index + 1).cloned();
+		next
+	}
+
+	fn claim_element(node: &Self::Node, namespace: Namespace, tag: &str) -> Option<Self::Element> {
+		let matches = node.tag() == Some(tag) && node.namespace() == Some(namespace);
+		matches.then(|| node.clone())
+	}
+
+	fn claim_text(node: &Self::Node, data: &str) -> Option<Self::Text> {
+		let MockNodeKind::Text(current) = &node.0.kind else {
+			return None;
+		};
+
+		if *current.borrow() == data {
+			return Some(node.clone());
+		}
+
+		// Mimic `Text::splitText` for text merged by the HTML parser
+		if !data.is_empty() && current.borrow().starts_with(data) {
+			let rest = current.borrow()[data.len()..].to_owned();
+			current.replace(data.to_owned());
+
+			let dom = MockDom {
+				log: node.0.log.clone(),
+				globals: Default::default(),
+			};
+			let rest = dom.create_text(&rest);
+			Self::insert(MockCursor::After(node.clone()), &rest);
+			return Some(node.clone());
+		}
+
+		None
+	}
+}
+
+impl HtmlBackend for MockDom {
+	type Listener = usize;
+
+	fn set_attribute(&self, element: &Self::Element, name: &str, value: &str) {
+		element
+			.element()
+			.attributes
+			.borrow_mut()
+			.insert(name.to_owned(), value.to_owned());
+
+		self.log.record(Mutation::SetAttribute {
+			id: element.id(),
+			name: name.to_owned(),
+			value: value.to_owned(),
+		});
+	}
+
+	fn remove_attribute(&self, element: &Self::Element, name: &str) {
+		element.element().attributes.borrow_mut().remove(name);
+		if name == "style" {
+			element.element().style.borrow_mut().clear();
+		}
+		self.log.record(Mutation::RemoveAttribute {
+			id: element.id(),
+			name: name.to_owned(),
+		});
+	}
+
+	fn set_property(&self, element: &Self::Element, name: &str, value: &str) {
+		element
+			.element()
+			.style
+			.borrow_mut()
+			.insert(name.to_owned(), value.to_owned());
+
+		self.log.record(Mutation::SetProperty {
+			id: element.id(),
+			name: name.to_owned(),
+			value: value.to_owned(),
+		});
+	}
+
+	fn remove_property(&self, element: &Self::Element, name: &str) {
+		element.element().style.borrow_mut().remove(name);
+		self.log.record(Mutation::RemoveProperty {
+			id: element.id(),
+			name: name.to_owned(),
+		});
+	}
+
+	// Like in a real DOM, the class list is backed by the `class` attribute
+
+	fn add_class(&self, element: &Self::Element, class: &str) {
+		let mut attributes = element.element().attributes.borrow_mut();
+		let current = attributes.get("class").map(|c| c.as_str()).unwrap_or("");
+		if !current.split_ascii_whitespace().any(|c| c == class) {
+			let next = if current.is_empty() {
+				class.to_owned()
+			} else {
+				format!("{} {}", current, class)
+			};
+			attributes.insert("class".to_owned(), next);
+		}
+
+		self.log.record(Mutation::AddClass {
+			id: element.id(),
+			class: class.to_owned(),
+		});
+	}
+
+	fn remove_class(&self, element: &Self::Element, class: &str) {
+		let mut attributes = element.element().attributes.borrow_mut();
+		if let Some(current) = attributes.get("class") {
+			let next = current
+				.split_ascii_whitespace()
+				.filter(|c| *c != class)
+				.collect::<Vec<_>>()
+				.join(" ");
+
+			if next.is_empty() {
+				attributes.remove("class");
+			} else {
+				attributes.insert("class".to_owned(), next);
+			}
+		}
+
+		self.log.record(Mutation::RemoveClass {
+			id: element.id(),
+			class: class.to_owned(),
+		});
+	}
+
+	fn set_dom_property(&self, element: &Self::Element, name: &str, value: &PropValue) {
+		element
+			.element()
+			.properties
+			.borrow_mut()
+			.insert(name.to_owned(), value.clone().into_owned());
+
+		self.log.record(Mutation::SetDomProperty {
+			id: element.id(),
+			name: name.to_owned(),
+			value: value.to_string(),
+		});
+	}
+
+	fn get_dom_property(&self, element: &Self::Element, name: &str) -> Option<PropValue<'static>> {
+		element.property(name)
+	}
+
+	fn attribute_names(&self, element: &Self::Element) -> Vec<String> {
+		let element = element.element();
+		let mut names: Vec<_> = element.attributes.borrow().keys().cloned().collect();
+		if !element.style.borrow().is_empty() {
+			names.push("style".to_owned());
+		}
+		names
+	}
+
+	fn add_listener(
+		&self,
+		tree: &Tree<Self>,
+		event: &'static str,
+		options: ListenerOptions,
+		handler: Rc<dyn Fn(Self::Event)>,
+	) -> Self::Listener {
+		self.listen(&tree.closest_node(), event, options, handler)
+	}
+
+	fn remove_listener(&self, tree: &Tree<Self>, listener: Self::Listener) {
+		self.unlisten(&tree.closest_node(), listener)
+	}
+
+	fn listen_global(
+		&self,
+		target: GlobalTarget,
+		event: &'static str,
+		options: ListenerOptions,
+		handler: Rc<dyn Fn(Self::Event)>,
+	) -> Option<Self::Listener> {
+		Some(self.listen(&self.global(target), event, options, handler))
+	}
+
+	fn unlisten_global(&self, target: GlobalTarget, listener: Self::Listener) {
+		self.unlisten(&self.global(target), listener)
+	}
+
+	fn mount_stylesheet(&self, sheet: &'static Stylesheet) {
+		self.log.styles.borrow_mut().acquire(sheet, || {
+			self.log.record(Mutation::MountStyle {
+				class: sheet.class().to_owned(),
+			})
+		});
+	}
+
+	fn unmount_stylesheet(&self, sheet: &'static Stylesheet) {
+		if self.log.styles.borrow_mut().release(sheet).is_some() {
+			self.log.record(Mutation::UnmountStyle {
+				class: sheet.class().to_owned(),
+			})
+		}
+	}
+
+	/// Runs the capture phase from the root down to the target, then the bubble phase
+	fn dispatch_event(&self, element: &Self::Element, event: Self::Event) {
+		event.0.target.replace(Some(element.clone()));
+
+		let mut path = vec![element.clone()];
+		while let Some(parent) = path.last().unwrap().parent() {
+			path.push(parent);
+		}
+
+		for node in path.iter().rev() {
+			node.fire(&event, true);
+			if event.0.stopped.get() {
+				return;
+			}
+		}
+
+		for node in path.iter() {
+			node.fire(&event, false);
+			if event.0.stopped.get() || !event.0.bubbles {
+				return;
+			}
+		}
+	}
+}
+
+/// A [`MockDom`] counterpart of [`crate::web::root::Root`].
+pub struct MockRoot<M>
+where
+	M: Markup<MockDom>,
+{
+	tree: Tree<MockDom>,
+	markup: M,
+	mismatches: Vec<HydrationMismatch>,
+}
+
+impl<M> MockRoot<M>
+where
+	M: Markup<MockDom>,
+{
+	pub fn render(markup: M) -> MockRoot<M> {
+		Self::render_in(MockDom::new(), markup)
+	}
+
+	pub fn render_in(dom: MockDom, mut markup: M) -> MockRoot<M> {
+		let tree = Tree::root(dom.container(), dom);
+		render_subtree(&mut markup, &tree);
+		MockRoot {
+			tree,
+			markup,
+			mismatches: Vec::new(),
+		}
+	}
+
+	/// Hydrates the existing children of `container`, see [`crate::web::root::Root::hydrate`]
+	pub fn hydrate(dom: MockDom, mut markup: M, container: MockNode) -> MockRoot<M> {
+		let tree = Tree::root(container, dom);
+		let mismatches = hydrate(&mut markup, &tree);
+		MockRoot {
+			tree,
+			markup,
+			mismatches,
+		}
+	}
+
+	pub fn mismatches(&self) -> &[HydrationMismatch] {
+		&self.mismatches
+	}
+
+	pub fn dom(&self) -> &MockDom {
+		&self.tree.backend
+	}
+
+	pub fn tree(&self) -> &Tree<MockDom> {
+		&self.tree
+	}
+
+	pub fn container(&self) -> MockNode {
+		self.tree.node().clone()
+	}
+
+	pub fn inner_html(&self) -> String {
+		self.container().inner_html()
+	}
+
+	pub fn update(&mut self, mut next: M) {
+		if M::dynamic() {
+			next.diff(&mut self.markup, &subtree::<M, _>(&self.tree));
+		}
+
+		self.markup = next;
+	}
+
+	pub fn unmount(mut self) {
+		self.markup.drop(&subtree::<M, _>(&self.tree), true);
+		self.tree.clear();
+	}
+}
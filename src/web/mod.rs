@@ -1,37 +1,65 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 pub use callback::Callback;
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{JsCast, JsValue};
 use web_sys::Element;
 
+use self::css::{StyleRegistry, Stylesheet};
+use self::event::{ListenerOptions, WebListener};
+use self::global::{GlobalTarget, ListenerGuard};
 use self::helpers::cursor::Cursor;
-use crate::{Backend, Markup};
+use self::prop::PropValue;
+use crate::tree::Tree;
+use crate::{Backend, Markup, Namespace};
 
 pub mod attr;
 pub mod bump;
 pub mod callback;
+pub mod clock;
 pub mod context;
+pub mod css;
+pub mod delegate;
 pub mod dispatch;
 pub mod effect;
 pub mod event;
+pub mod events;
+pub mod global;
 pub mod helpers;
+pub mod mock;
 pub mod portal;
+pub mod prop;
+pub mod rate;
 pub mod reactive;
 pub mod root;
+pub mod router;
 pub mod routing;
+pub mod serialize;
 pub mod ssr;
 pub mod stateful;
+pub mod stream;
 pub mod style;
 pub mod tag;
+pub mod task;
 pub mod text;
 
 pub mod prelude {
 	pub use crate::reference::reference;
-	pub use crate::web::attr::{attr, classlist, classname};
+	pub use crate::web::attr::{
+		attr, attrs, bool_attr, button_type, classes, classlist, classname, input_type, typed_attr,
+		ButtonType, Const, InputType,
+	};
 	pub use crate::web::callback::Callback;
+	pub use crate::web::css::css;
 	pub use crate::web::event::on;
+	pub use crate::web::events::handlers::*;
+	pub use crate::web::global::{on_document, on_window};
 	pub use crate::web::html::*;
+	pub use crate::web::prop::prop;
 	pub use crate::web::reactive::reactive;
 	pub use crate::web::stateful::stateful;
 	pub use crate::web::style::property;
+	pub use crate::web::tag::{mathml, svg};
 	pub use crate::web::HtmlBackend;
 	pub use crate::{dynamic, Markup};
 }
@@ -48,6 +76,51 @@ pub trait HtmlBackend: Backend {
 	fn remove_property(&self, element: &Self::Element, name: &str);
 	fn add_class(&self, element: &Self::Element, class: &str);
 	fn remove_class(&self, element: &Self::Element, class: &str);
+	fn set_dom_property(&self, element: &Self::Element, name: &str, value: &PropValue);
+	/// Current value of the property, `None` if the backend can't read it
+	fn get_dom_property(&self, element: &Self::Element, name: &str) -> Option<PropValue<'static>>;
+
+	/// Names of the attributes of the element, used to remove the ones left after hydration
+	fn attribute_names(&self, _element: &Self::Element) -> Vec<String> {
+		Vec::new()
+	}
+
+	/// Handle of a listener added with [`HtmlBackend::add_listener`]
+	type Listener: 'static;
+
+	/// Adds an event listener to the closest element of the tree
+	fn add_listener(
+		&self,
+		tree: &Tree<Self>,
+		event: &'static str,
+		options: ListenerOptions,
+		handler: Rc<dyn Fn(Self::Event)>,
+	) -> Self::Listener;
+
+	fn remove_listener(&self, tree: &Tree<Self>, listener: Self::Listener);
+
+	/// Dispatches the event on the element, used to simulate user input
+	fn dispatch_event(&self, element: &Self::Element, event: Self::Event);
+
+	/// Listens to `window` or `document` events, `None` if the backend has no such targets
+	fn listen_global(
+		&self,
+		_target: GlobalTarget,
+		_event: &'static str,
+		_options: ListenerOptions,
+		_handler: Rc<dyn Fn(Self::Event)>,
+	) -> Option<Self::Listener> {
+		None
+	}
+
+	/// Removes a listener added with [`HtmlBackend::listen_global`]
+	fn unlisten_global(&self, _target: GlobalTarget, _listener: Self::Listener) {}
+
+	/// Called for every element that uses the stylesheet
+	fn mount_stylesheet(&self, _sheet: &'static Stylesheet) {}
+
+	/// Called when an element that used the stylesheet is gone
+	fn unmount_stylesheet(&self, _sheet: &'static Stylesheet) {}
 }
 
 #[derive(Default, Clone, Debug)]
@@ -55,6 +128,8 @@ pub struct WebSys;
 impl WebSys {}
 
 impl HtmlBackend for WebSys {
+	type Listener = WebListener;
+
 	fn set_attribute(&self, node: &Self::Element, name: &str, value: &str) {
 		node.set_attribute(name, value).unwrap()
 	}
@@ -84,6 +159,88 @@ impl HtmlBackend for WebSys {
 	fn remove_class(&self, node: &Self::Element, class: &str) {
 		node.class_list().remove_1(class).unwrap();
 	}
+
+	fn set_dom_property(&self, node: &Self::Element, name: &str, value: &PropValue) {
+		let value = match value {
+			PropValue::Str(s) => JsValue::from_str(s),
+			PropValue::Bool(b) => JsValue::from_bool(*b),
+			PropValue::Number(n) => JsValue::from_f64(*n),
+		};
+
+		js_sys::Reflect::set(node, &JsValue::from_str(name), &value).unwrap();
+	}
+
+	fn get_dom_property(&self, node: &Self::Element, name: &str) -> Option<PropValue<'static>> {
+		let value = js_sys::Reflect::get(node, &JsValue::from_str(name)).ok()?;
+		if let Some(s) = value.as_string() {
+			Some(PropValue::Str(s.into()))
+		} else if let Some(b) = value.as_bool() {
+			Some(PropValue::Bool(b))
+		} else {
+			value.as_f64().map(PropValue::Number)
+		}
+	}
+
+	fn attribute_names(&self, node: &Self::Element) -> Vec<String> {
+		node.get_attribute_names()
+			.iter()
+			.filter_map(|name| name.as_string())
+			.collect()
+	}
+
+	fn listen_global(
+		&self,
+		target: GlobalTarget,
+		event: &'static str,
+		options: ListenerOptions,
+		handler: Rc<dyn Fn(Self::Event)>,
+	) -> Option<Self::Listener> {
+		Some(WebListener::Global(ListenerGuard::new(
+			target, event, options, handler,
+		)))
+	}
+
+	fn unlisten_global(&self, _target: GlobalTarget, listener: Self::Listener) {
+		// The guard removes the listener
+		std::mem::drop(listener)
+	}
+
+	fn add_listener(
+		&self,
+		tree: &Tree<Self>,
+		event: &'static str,
+		options: ListenerOptions,
+		handler: Rc<dyn Fn(Self::Event)>,
+	) -> Self::Listener {
+		WebListener::add(tree, event, options, handler)
+	}
+
+	fn remove_listener(&self, tree: &Tree<Self>, listener: Self::Listener) {
+		listener.remove(tree)
+	}
+
+	fn dispatch_event(&self, element: &Self::Element, event: Self::Event) {
+		element.dispatch_event(&event).unwrap();
+	}
+
+	fn mount_stylesheet(&self, sheet: &'static Stylesheet) {
+		STYLES.with(|styles| {
+			styles
+				.borrow_mut()
+				.acquire(sheet, || css::mount_style_element(sheet))
+		})
+	}
+
+	fn unmount_stylesheet(&self, sheet: &'static Stylesheet) {
+		if let Some(element) = STYLES.with(|styles| styles.borrow_mut().release(sheet)) {
+			element.remove();
+		}
+	}
+}
+
+thread_local! {
+	// `<style>` elements mounted by [`WebSys`]
+	static STYLES: RefCell<StyleRegistry<Element>> = Default::default();
 }
 
 impl Backend for WebSys {
@@ -109,6 +266,11 @@ impl Backend for WebSys {
 		}
 	}
 
+	fn move_node(cursor: Cursor, node: &Self::Node) {
+		// `insertNode` moves the node if it's already attached
+		cursor.range.insert_node(node).unwrap()
+	}
+
 	fn cursor_after(node: &Self::Node) -> Self::Cursor {
 		if let Ok(node) = Cursor::after(node) {
 			node
@@ -127,6 +289,15 @@ impl Backend for WebSys {
 		doc.create_element(tag).unwrap().unchecked_into()
 	}
 
+	fn create_element_ns(&self, namespace: Namespace, tag: &'static str) -> Self::Element {
+		if namespace == Namespace::Html {
+			return self.create_element(tag);
+		}
+
+		let doc = web_sys::window().unwrap().document().unwrap();
+		doc.create_element_ns(Some(namespace.uri()), tag).unwrap()
+	}
+
 	fn create_text(&self, data: &str) -> Self::Text {
 		let doc = web_sys::window().unwrap().document().unwrap();
 		doc.create_text_node(data)
@@ -157,4 +328,36 @@ impl Backend for WebSys {
 	fn print_node(node: &Self::Node) {
 		web_sys::console::log_1(node);
 	}
+
+	fn first_child(element: &Self::Element) -> Option<Self::Node> {
+		element.first_child()
+	}
+
+	fn next_sibling(node: &Self::Node) -> Option<Self::Node> {
+		node.next_sibling()
+	}
+
+	fn claim_element(node: &Self::Node, namespace: Namespace, tag: &str) -> Option<Self::Element> {
+		let element = node.dyn_ref::<Element>()?;
+		let matches = element.local_name() == tag
+			&& element.namespace_uri().as_deref() == Some(namespace.uri());
+
+		matches.then(|| element.clone())
+	}
+
+	fn claim_text(node: &Self::Node, data: &str) -> Option<Self::Text> {
+		let text = node.dyn_ref::<web_sys::Text>()?;
+		let current = text.data();
+		if current == data {
+			return Some(text.clone());
+		}
+
+		// The HTML parser merges adjacent text nodes, split them back
+		if !data.is_empty() && current.starts_with(data) {
+			text.split_text(data.encode_utf16().count() as u32).ok()?;
+			return Some(text.clone());
+		}
+
+		None
+	}
 }
@@ -1,6 +1,7 @@
 use wasm_bindgen::JsCast;
 use web_sys::Element;
 
+use super::delegate::Delegation;
 use super::helpers::dom::DOCUMENT;
 use crate::web::WebSys;
 use crate::{init_subtree, subtree, Markup};
@@ -24,6 +25,12 @@ impl<M: Markup<WebSys>> Markup<WebSys> for Portal<M> {
 		tree.name.replace(std::borrow::Cow::Borrowed("Portal"));
 
 		tree.set_node(self.element.clone().unchecked_into());
+
+		// Events don't bubble from the portal to the parent container
+		if Delegation::find(tree).is_some() {
+			tree.data_mut().set(Delegation::new(self.element.clone()));
+		}
+
 		let subtree = init_subtree::<M, _>(tree);
 		self.markup.render(&subtree);
 	}
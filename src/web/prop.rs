@@ -0,0 +1,232 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use super::attr::claim_attribute;
+use super::{HtmlBackend, StringLike};
+use crate::tree::Tree;
+use crate::Markup;
+
+/// Value of a DOM property, e.g. `input.value` or `input.checked`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropValue<'a> {
+	Str(Cow<'a, str>),
+	Bool(bool),
+	Number(f64),
+}
+
+impl PropValue<'_> {
+	pub fn into_owned(self) -> PropValue<'static> {
+		match self {
+			PropValue::Str(s) => PropValue::Str(Cow::Owned(s.into_owned())),
+			PropValue::Bool(b) => PropValue::Bool(b),
+			PropValue::Number(n) => PropValue::Number(n),
+		}
+	}
+
+	/// The value a property of the same type has by default
+	pub fn reset(&self) -> PropValue<'static> {
+		match self {
+			PropValue::Str(_) => PropValue::Str(Cow::Borrowed("")),
+			PropValue::Bool(_) => PropValue::Bool(false),
+			PropValue::Number(_) => PropValue::Number(0.0),
+		}
+	}
+
+	/// The DOM converts values to the property type, so `"1"` and `1` are the same
+	pub fn same_as(&self, other: &PropValue) -> bool {
+		match (self, other) {
+			(PropValue::Str(a), PropValue::Str(b)) => a == b,
+			(PropValue::Bool(a), PropValue::Bool(b)) => a == b,
+			(PropValue::Number(a), PropValue::Number(b)) => a == b,
+			(a, b) => a.to_string() == b.to_string(),
+		}
+	}
+
+	/// Attribute value for the property, `None` if the attribute should be removed
+	pub fn to_attribute(&self) -> Option<Cow<'_, str>> {
+		match self {
+			PropValue::Str(s) => Some(Cow::Borrowed(s.as_ref())),
+			PropValue::Bool(true) => Some(Cow::Borrowed("")),
+			PropValue::Bool(false) => None,
+			PropValue::Number(n) => Some(Cow::Owned(n.to_string())),
+		}
+	}
+}
+
+impl Display for PropValue<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PropValue::Str(s) => f.write_str(s),
+			PropValue::Bool(b) => b.fmt(f),
+			PropValue::Number(n) => n.fmt(f),
+		}
+	}
+}
+
+impl<'a> From<&'a str> for PropValue<'a> {
+	fn from(value: &'a str) -> Self {
+		PropValue::Str(Cow::Borrowed(value))
+	}
+}
+
+impl From<String> for PropValue<'static> {
+	fn from(value: String) -> Self {
+		PropValue::Str(Cow::Owned(value))
+	}
+}
+
+impl<'a> From<Cow<'a, str>> for PropValue<'a> {
+	fn from(value: Cow<'a, str>) -> Self {
+		PropValue::Str(value)
+	}
+}
+
+impl From<bool> for PropValue<'static> {
+	fn from(value: bool) -> Self {
+		PropValue::Bool(value)
+	}
+}
+
+macro impl_number($($ty:ty),*) {
+	$(
+		impl From<$ty> for PropValue<'static> {
+			fn from(value: $ty) -> Self {
+				PropValue::Number(value as f64)
+			}
+		}
+	)*
+}
+
+impl_number!(f64, f32, i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+
+/// Properties reflected by an attribute with the same name
+const SAME_NAME: &[&str] = &[
+	"accept",
+	"action",
+	"alt",
+	"autofocus",
+	"autoplay",
+	"cols",
+	"controls",
+	"dir",
+	"disabled",
+	"download",
+	"draggable",
+	"enctype",
+	"hidden",
+	"href",
+	"id",
+	"label",
+	"lang",
+	"loop",
+	"max",
+	"method",
+	"min",
+	"multiple",
+	"muted",
+	"name",
+	"open",
+	"pattern",
+	"placeholder",
+	"poster",
+	"preload",
+	"rel",
+	"required",
+	"reversed",
+	"rows",
+	"size",
+	"span",
+	"spellcheck",
+	"src",
+	"start",
+	"step",
+	"target",
+	"title",
+	"type",
+	"wrap",
+];
+
+/// Attribute that reflects the property, used when rendering to HTML.
+/// `None` for properties without an attribute, like `selectedIndex` or `scrollTop`.
+pub fn reflected_attribute(name: &str) -> Option<Cow<'static, str>> {
+	let attribute = match name {
+		"className" => "class",
+		"htmlFor" => "for",
+		"defaultValue" | "value" => "value",
+		"defaultChecked" | "checked" => "checked",
+		"defaultSelected" | "selected" => "selected",
+		"accessKey" | "autocomplete" | "colSpan" | "contentEditable" | "enterKeyHint"
+		| "formAction" | "formMethod" | "formNoValidate" | "inputMode" | "maxLength"
+		| "minLength" | "noValidate" | "readOnly" | "rowSpan" | "tabIndex" | "useMap" => {
+			return Some(Cow::Owned(name.to_ascii_lowercase()))
+		}
+		_ => *SAME_NAME.iter().find(|same| **same == name)?,
+	};
+
+	Some(Cow::Borrowed(attribute))
+}
+
+pub struct Prop<'a, S: StringLike> {
+	name: S,
+	value: PropValue<'a>,
+}
+
+/// Sets a DOM property, as opposed to an attribute.
+///
+/// The value is compared with the live DOM value on every diff,
+/// so user input is overwritten, e.g. for controlled inputs.
+pub fn prop<'a, S: StringLike>(name: S, value: impl Into<PropValue<'a>>) -> Prop<'a, S> {
+	Prop {
+		name,
+		value: value.into(),
+	}
+}
+
+impl<B: HtmlBackend, S: StringLike> Markup<B> for Prop<'_, S> {
+	fn has_own_node() -> bool {
+		false
+	}
+
+	fn dynamic() -> bool {
+		true
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		// The server renders reflected properties as attributes
+		if let Some(name) = reflected_attribute(self.name.as_ref()) {
+			claim_attribute(tree, &name);
+		}
+
+		let element = B::node_to_element(tree.closest_node()).unwrap();
+		tree.backend
+			.set_dom_property(&element, self.name.as_ref(), &self.value);
+	}
+
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		let element = B::node_to_element(tree.closest_node()).unwrap();
+		if prev.name.as_ref() != self.name.as_ref() {
+			tree.backend
+				.set_dom_property(&element, prev.name.as_ref(), &prev.value.reset());
+		} else {
+			let unchanged = match tree.backend.get_dom_property(&element, self.name.as_ref()) {
+				Some(live) => live.same_as(&self.value),
+				None => prev.value.same_as(&self.value),
+			};
+
+			if unchanged {
+				return;
+			}
+		}
+
+		tree.backend
+			.set_dom_property(&element, self.name.as_ref(), &self.value);
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		if should_unmount {
+			let element = B::node_to_element(tree.closest_node()).unwrap();
+			tree.backend
+				.set_dom_property(&element, self.name.as_ref(), &self.value.reset());
+		}
+	}
+}
@@ -0,0 +1,354 @@
+//! Debounced, throttled and animation frame callbacks.
+//!
+//! ```ignore
+//! let search = cx.debounced(300, cx.wrap(|cx, (query,): (String,)| cx.dispatch(Search(query))));
+//! ```
+//!
+//! Inside of a component pending calls are cancelled when it unmounts.
+
+use std::any::TypeId;
+use std::cell::{Cell, RefCell};
+use std::marker::Tuple;
+use std::panic::Location;
+use std::rc::{Rc, Weak};
+
+use super::clock::{BrowserClock, Clock, SharedClock, TimerId};
+use super::context::{call_site_key, Extension, StatefulContext, WithEffects, WithMemo};
+use super::Callback;
+use crate::Backend;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rate {
+	/// Waits until the calls stop for the given number of milliseconds
+	Debounce(u32),
+	/// At most one call per interval, the last call of an interval is delayed until its end
+	Throttle(u32),
+	/// At most one call per animation frame
+	AnimationFrame,
+}
+
+struct Pending<Args> {
+	timer: Option<TimerId>,
+	args: Option<Args>,
+	last_call: Option<f64>,
+}
+
+/// Calls `func` with the latest arguments according to the [`Rate`]
+pub struct RateLimited<F, Args> {
+	this: Weak<Self>,
+	clock: Rc<dyn Clock>,
+	rate: Cell<Rate>,
+	func: RefCell<Rc<F>>,
+	pending: RefCell<Pending<Args>>,
+}
+
+impl<F, Args> RateLimited<F, Args>
+where
+	F: Fn<Args> + 'static,
+	Args: Tuple + 'static,
+{
+	pub fn new(clock: Rc<dyn Clock>, rate: Rate, func: F) -> Rc<Self> {
+		Rc::new_cyclic(|this| RateLimited {
+			this: this.clone(),
+			clock,
+			rate: Cell::new(rate),
+			func: RefCell::new(Rc::new(func)),
+			pending: RefCell::new(Pending {
+				timer: None,
+				args: None,
+				last_call: None,
+			}),
+		})
+	}
+
+	/// Drops the pending call, if any
+	pub fn cancel(&self) {
+		let timer = {
+			let mut pending = self.pending.borrow_mut();
+			pending.args = None;
+			pending.timer.take()
+		};
+
+		if let Some(timer) = timer {
+			self.clock.cancel(timer)
+		}
+	}
+
+	fn push(&self, args: Args) {
+		match self.rate.get() {
+			Rate::Debounce(delay) => {
+				self.cancel();
+				self.pending.borrow_mut().args = Some(args);
+				self.schedule(Some(delay));
+			}
+			Rate::Throttle(interval) => {
+				let now = self.clock.now();
+				let mut pending = self.pending.borrow_mut();
+				let elapsed = pending.last_call.map(|last| now - last);
+
+				if pending.timer.is_some() {
+					pending.args = Some(args);
+				} else if let Some(elapsed) = elapsed.filter(|e| *e < interval as f64) {
+					pending.args = Some(args);
+					std::mem::drop(pending);
+					self.schedule(Some((interval as f64 - elapsed).ceil() as u32));
+				} else {
+					pending.last_call = Some(now);
+					std::mem::drop(pending);
+					self.invoke(args);
+				}
+			}
+			Rate::AnimationFrame => {
+				let scheduled = {
+					let mut pending = self.pending.borrow_mut();
+					pending.args = Some(args);
+					pending.timer.is_some()
+				};
+
+				if !scheduled {
+					self.schedule(None);
+				}
+			}
+		}
+	}
+
+	// `None` schedules an animation frame
+	fn schedule(&self, delay: Option<u32>) {
+		let this = self.this.clone();
+		let fire = Box::new(move || {
+			if let Some(this) = this.upgrade() {
+				this.fire()
+			}
+		});
+
+		let timer = match delay {
+			Some(delay) => self.clock.set_timeout(delay, fire),
+			None => self.clock.request_frame(fire),
+		};
+
+		self.pending.borrow_mut().timer = Some(timer);
+	}
+
+	fn fire(&self) {
+		let args = {
+			let mut pending = self.pending.borrow_mut();
+			pending.timer = None;
+			if let Rate::Throttle(_) = self.rate.get() {
+				pending.last_call = Some(self.clock.now());
+			}
+			pending.args.take()
+		};
+
+		if let Some(args) = args {
+			self.invoke(args)
+		}
+	}
+
+	fn invoke(&self, args: Args) {
+		// we clone here to allow `func` to trigger a render that replaces it
+		let func = { self.func.borrow().clone() };
+		func.call(args);
+	}
+}
+
+impl<F, Args> Fn<Args> for RateLimited<F, Args>
+where
+	F: Fn<Args> + 'static,
+	Args: Tuple + 'static,
+{
+	extern "rust-call" fn call(&self, args: Args) {
+		self.push(args)
+	}
+}
+
+impl<F, Args> FnMut<Args> for RateLimited<F, Args>
+where
+	F: Fn<Args> + 'static,
+	Args: Tuple + 'static,
+{
+	extern "rust-call" fn call_mut(&mut self, args: Args) {
+		self.push(args)
+	}
+}
+
+impl<F, Args> FnOnce<Args> for RateLimited<F, Args>
+where
+	F: Fn<Args> + 'static,
+	Args: Tuple + 'static,
+{
+	type Output = ();
+
+	extern "rust-call" fn call_once(self, args: Args) {
+		self.push(args)
+	}
+}
+
+impl<F, Args> Drop for RateLimited<F, Args> {
+	fn drop(&mut self) {
+		if let Some(timer) = self.pending.get_mut().timer.take() {
+			self.clock.cancel(timer)
+		}
+	}
+}
+
+impl<B: Backend + 'static, E: 'static> StatefulContext<B, E>
+where
+	E: Extension<WithMemo>,
+	E: Extension<WithEffects<B, E>>,
+{
+	#[track_caller]
+	pub fn debounced<Args, F>(&self, delay: u32, func: F) -> Callback<dyn Fn<Args, Output = ()>>
+	where
+		Args: Tuple + 'static,
+		F: Fn<Args> + 'static,
+	{
+		self.rate_limited(Rate::Debounce(delay), func)
+	}
+
+	#[track_caller]
+	pub fn throttled<Args, F>(&self, interval: u32, func: F) -> Callback<dyn Fn<Args, Output = ()>>
+	where
+		Args: Tuple + 'static,
+		F: Fn<Args> + 'static,
+	{
+		self.rate_limited(Rate::Throttle(interval), func)
+	}
+
+	#[track_caller]
+	pub fn animation_frame<Args, F>(&self, func: F) -> Callback<dyn Fn<Args, Output = ()>>
+	where
+		Args: Tuple + 'static,
+		F: Fn<Args> + 'static,
+	{
+		self.rate_limited(Rate::AnimationFrame, func)
+	}
+
+	/// The limiter lives as long as the component, `func` is replaced on every render
+	#[track_caller]
+	pub fn rate_limited<Args, F>(&self, rate: Rate, func: F) -> Callback<dyn Fn<Args, Output = ()>>
+	where
+		Args: Tuple + 'static,
+		F: Fn<Args> + 'static,
+	{
+		let key = call_site_key::<RateLimited<F, Args>>();
+		let with_memo: &WithMemo = self.ext.get();
+		with_memo.check_key(key, Location::caller());
+
+		let existing = with_memo
+			.memo
+			.borrow()
+			.try_with_key::<Rc<RateLimited<F, Args>>>(key);
+
+		let limiter = match existing {
+			Some(limiter) => {
+				limiter.rate.set(rate);
+				*limiter.func.borrow_mut() = Rc::new(func);
+				limiter
+			}
+			None => {
+				let limiter = RateLimited::new(self.clock(), rate, func);
+				with_memo
+					.memo
+					.borrow_mut()
+					.set_with_key(key, limiter.clone());
+				limiter
+			}
+		};
+
+		// Cancels the pending call on unmount
+		self.effect_once_clean({
+			let limiter = limiter.clone();
+			move |_| move |_| limiter.cancel()
+		});
+
+		Callback(limiter, TypeId::of::<F>())
+	}
+
+	/// Clock provided with [`SharedClock`]. The browser clock is the default on wasm,
+	/// other targets have to provide one, e.g. a [`ManualClock`](super::clock::ManualClock).
+	pub fn clock(&self) -> Rc<dyn Clock> {
+		match self.try_env::<Rc<SharedClock>>() {
+			Some(clock) => clock.0.clone(),
+			None if cfg!(target_arch = "wasm32") => Rc::new(BrowserClock::default()),
+			None => panic!("There is no browser clock outside of wasm, provide a SharedClock"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::RefCell;
+	use std::rc::Rc;
+
+	use super::{Rate, RateLimited};
+	use crate::web::clock::ManualClock;
+
+	fn limited(rate: Rate) -> (ManualClock, Rc<RefCell<Vec<u32>>>, impl Fn(u32)) {
+		let clock = ManualClock::new();
+		let calls = Rc::new(RefCell::new(Vec::new()));
+		let limiter = RateLimited::new(Rc::new(clock.clone()), rate, {
+			let calls = calls.clone();
+			move |v: u32| calls.borrow_mut().push(v)
+		});
+
+		(clock, calls, move |v| limiter(v))
+	}
+
+	#[test]
+	fn test_debounce() {
+		let (clock, calls, func) = limited(Rate::Debounce(100));
+
+		func(1);
+		clock.advance(50.0);
+		func(2);
+		clock.advance(50.0);
+		func(3);
+		clock.advance(99.0);
+		assert!(calls.borrow().is_empty());
+
+		clock.advance(1.0);
+		assert_eq!(*calls.borrow(), vec![3]);
+	}
+
+	#[test]
+	fn test_throttle() {
+		let (clock, calls, func) = limited(Rate::Throttle(100));
+
+		func(1);
+		clock.advance(10.0);
+		func(2);
+		func(3);
+		assert_eq!(*calls.borrow(), vec![1]);
+
+		clock.advance(90.0);
+		assert_eq!(*calls.borrow(), vec![1, 3]);
+
+		clock.advance(100.0);
+		func(4);
+		assert_eq!(*calls.borrow(), vec![1, 3, 4]);
+	}
+
+	#[test]
+	fn test_animation_frame() {
+		let (clock, calls, func) = limited(Rate::AnimationFrame);
+
+		func(1);
+		func(2);
+		assert_eq!(clock.pending(), 1);
+
+		clock.frame();
+		assert_eq!(*calls.borrow(), vec![2]);
+	}
+
+	#[test]
+	fn test_drop_cancels() {
+		let (clock, calls, func) = limited(Rate::Debounce(100));
+
+		func(1);
+		std::mem::drop(func);
+		assert_eq!(clock.pending(), 0);
+
+		clock.advance(100.0);
+		assert!(calls.borrow().is_empty());
+	}
+}
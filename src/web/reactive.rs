@@ -5,6 +5,7 @@ use std::error::{request_ref, Error};
 use std::fmt::Display;
 use std::hash::Hash;
 use std::marker::PhantomData;
+use std::panic::Location;
 use std::rc::{Rc, Weak};
 
 use observe::{Dependencies, Derived, Evaluation, Invalid, State, CHANGED};
@@ -12,8 +13,8 @@ use wasm_bindgen::prelude::{wasm_bindgen, Closure};
 use wasm_bindgen::JsValue;
 
 use super::context::{
-	DynInit, Extension, HasContext, MaybeExtension, StatefulContext, WithArena, WithCycle,
-	WithEffects, WithMemo, WithState,
+	call_site_key, DynInit, Extension, HasContext, MaybeExtension, StatefulContext, WithArena,
+	WithCycle, WithEffects, WithMemo, WithState,
 };
 use super::WebSys;
 use crate::reference::Mutable;
@@ -78,25 +79,18 @@ impl<B: Backend, E> StatefulContext<B, E> {
 			.unwrap()
 	}
 
+	#[track_caller]
 	pub fn mutable<T>(&mut self, value: T) -> Mutable<T>
 	where
 		E: Extension<WithMemo>,
 		T: 'static,
 	{
-		let key = fxhash::hash64(&TypeId::of::<T>());
-		let state: &mut WithMemo = self.ext.get_mut();
-		// TODO: Store mutables separately to avoid double allocation
-		let item = state.memo.borrow_mut().try_with_key::<Mutable<T>>(key);
-
-		if let Some(item) = item {
-			return item;
-		} else {
-			let item = Mutable::new(value);
-			state.memo.borrow_mut().set_with_key(key, item.clone());
-			item
-		}
+		let key = call_site_key::<Mutable<T>>();
+		self.mutable_internal(key, || value)
 	}
 
+	/// Keyed by the tag instead of the call site
+	#[track_caller]
 	pub fn mutable_tagged<T, M>(&mut self, tag: &M, value: T) -> Mutable<T>
 	where
 		E: Extension<WithMemo>,
@@ -104,40 +98,21 @@ impl<B: Backend, E> StatefulContext<B, E> {
 		M: Hash + 'static,
 	{
 		let key = fxhash::hash64(&(TypeId::of::<T>(), TypeId::of::<M>(), fxhash::hash64(&tag)));
-
-		let state: &mut WithMemo = self.ext.get_mut();
-		// TODO: Store mutables separately to avoid double allocation
-		let item = state.memo.borrow_mut().try_with_key::<Mutable<T>>(key);
-
-		if let Some(item) = item {
-			return item;
-		} else {
-			let item = Mutable::new(value);
-			state.memo.borrow_mut().set_with_key(key, item.clone());
-			item
-		}
+		self.mutable_internal(key, || value)
 	}
 
+	#[track_caller]
 	pub fn mutable_with<T, F>(&mut self, func: F) -> Mutable<T>
 	where
 		E: Extension<WithMemo>,
 		T: 'static,
 		F: FnOnce() -> T,
 	{
-		let key = fxhash::hash64(&TypeId::of::<T>());
-		let state: &mut WithMemo = self.ext.get_mut();
-		// TODO: Store mutables separately to avoid double allocation
-		let item = state.memo.borrow_mut().try_with_key::<Mutable<T>>(key);
-
-		if let Some(item) = item {
-			return item;
-		} else {
-			let item = Mutable::new(func());
-			state.memo.borrow_mut().set_with_key(key, item.clone());
-			item
-		}
+		let key = call_site_key::<Mutable<T>>();
+		self.mutable_internal(key, func)
 	}
 
+	#[track_caller]
 	pub fn mutable_tagged_with<T, F, M>(&mut self, marker: &M, func: F) -> Mutable<T>
 	where
 		E: Extension<WithMemo>,
@@ -150,8 +125,19 @@ impl<B: Backend, E> StatefulContext<B, E> {
 			TypeId::of::<M>(),
 			fxhash::hash64(&marker),
 		));
+		self.mutable_internal(key, func)
+	}
 
+	#[track_caller]
+	fn mutable_internal<T, F>(&mut self, key: u64, func: F) -> Mutable<T>
+	where
+		E: Extension<WithMemo>,
+		T: 'static,
+		F: FnOnce() -> T,
+	{
 		let state: &mut WithMemo = self.ext.get_mut();
+		state.check_key(key, Location::caller());
+
 		// TODO: Store mutables separately to avoid double allocation
 		let item = state.memo.borrow_mut().try_with_key::<Mutable<T>>(key);
 
@@ -305,6 +291,16 @@ impl<B> MaybeExtension<WithEffects<B, Self>> for ReactiveExt<B> {
 	}
 }
 
+impl<B> MaybeExtension<WithMemo> for ReactiveExt<B> {
+	fn try_get(&self) -> Option<&WithMemo> {
+		Some(&self.memo)
+	}
+
+	fn try_get_mut(&mut self) -> Option<&mut WithMemo> {
+		Some(&mut self.memo)
+	}
+}
+
 impl<B> MaybeExtension<WithArena> for ReactiveExt<B> {
 	fn try_get(&self) -> Option<&WithArena> {
 		None
@@ -413,6 +409,7 @@ where
 	E: MaybeExtension<WithEffects<B, E>>,
 	E: MaybeExtension<WithReactions>,
 	E: MaybeExtension<WithArena>,
+	E: MaybeExtension<WithMemo>,
 {
 	pub fn update(&self) {
 		// Passing the same context
@@ -442,8 +439,16 @@ where
 			with_effects.reset_effects_alive();
 		}
 
+		if let Some(with_memo @ WithMemo { .. }) = component.context.ext.try_get() {
+			with_memo.reset_keys();
+		}
+
 		let mut next_markup = (component.factory)(&mut component.context);
 
+		if let Some(with_memo @ WithMemo { .. }) = component.context.ext.try_get() {
+			with_memo.drop_unused();
+		}
+
 		self.state.set(State::Valid);
 
 		if let Some(with_reactions @ WithReactions { .. }) = component.context.ext.try_get_mut() {
@@ -501,6 +506,7 @@ where
 	E: MaybeExtension<WithEffects<B, E>>,
 	E: MaybeExtension<WithReactions>,
 	E: MaybeExtension<WithArena>,
+	E: MaybeExtension<WithMemo>,
 {
 	fn update(&self) {
 		ReactiveComponent::update(self)
@@ -516,6 +522,7 @@ where
 	E: MaybeExtension<WithEffects<B, E>>,
 	E: MaybeExtension<WithReactions>,
 	E: MaybeExtension<WithArena>,
+	E: MaybeExtension<WithMemo>,
 {
 	fn invalidate(self: Rc<Self>, invalid: observe::Invalid) {
 		if matches!(self.state.get(), State::Valid) {
@@ -544,6 +551,7 @@ where
 	E: Extension<WithCycle<B, E>>,
 	E: MaybeExtension<WithReactions>,
 	E: MaybeExtension<WithArena>,
+	E: MaybeExtension<WithMemo>,
 	E: MaybeExtension<WithEffects<B, E>>,
 	E: DynInit + 'static,
 {
@@ -1,6 +1,8 @@
 use wasm_bindgen::JsCast;
 use web_sys::HtmlElement;
 
+use super::delegate::Delegation;
+use crate::hydrate::{hydrate, HydrationMismatch};
 use crate::tree::Tree;
 use crate::web::{Markup, WebSys};
 use crate::{console_log, render_subtree, subtree};
@@ -12,6 +14,7 @@ where
 	pub(crate) tree: Tree<WebSys>,
 	markup: M,
 	is_ephemeral: bool,
+	mismatches: Vec<HydrationMismatch>,
 }
 
 impl<M> Root<M>
@@ -27,6 +30,39 @@ where
 			markup,
 			tree,
 			is_ephemeral: false,
+			mismatches: Vec::new(),
+		}
+	}
+
+	/// Like [`Root::render`], but event listeners are delegated to the container,
+	/// see [`delegate`](super::delegate)
+	pub fn render_delegated(mut markup: M, container: HtmlElement) -> Root<M> {
+		let tree = Tree::root(container.clone().unchecked_into(), WebSys {});
+		tree.data_mut()
+			.set(Delegation::new(container.unchecked_into()));
+		console_log!("Render root subtree");
+
+		render_subtree(&mut markup, &tree);
+		Root {
+			markup,
+			tree,
+			is_ephemeral: false,
+			mismatches: Vec::new(),
+		}
+	}
+
+	/// Attaches the markup to the server-rendered children of `container`.
+	/// Nodes that don't match the markup are re-created and reported in [`Root::mismatches`].
+	pub fn hydrate(mut markup: M, container: HtmlElement) -> Root<M> {
+		let tree = Tree::root(container.unchecked_into(), WebSys {});
+		console_log!("Hydrate root subtree");
+
+		let mismatches = hydrate(&mut markup, &tree);
+		Root {
+			markup,
+			tree,
+			is_ephemeral: false,
+			mismatches,
 		}
 	}
 
@@ -44,12 +80,19 @@ where
 			markup,
 			tree,
 			is_ephemeral: true,
+			mismatches: Vec::new(),
 		}
 	}
 
 	pub fn portal(mut markup: M, container: HtmlElement, parent: &Tree<WebSys>) -> Root<M> {
 		let tree = Tree::new(parent);
-		tree.set_node(container.unchecked_into());
+		tree.set_node(container.clone().unchecked_into());
+
+		// Events don't bubble from the portal to the parent container
+		if Delegation::find(parent).is_some() {
+			tree.data_mut()
+				.set(Delegation::new(container.unchecked_into()));
+		}
 
 		markup.render(&tree);
 
@@ -57,9 +100,14 @@ where
 			markup,
 			tree,
 			is_ephemeral: true,
+			mismatches: Vec::new(),
 		}
 	}
 
+	pub fn mismatches(&self) -> &[HydrationMismatch] {
+		&self.mismatches
+	}
+
 	pub fn element(&self) -> HtmlElement {
 		self.tree.node().clone().unchecked_into()
 	}
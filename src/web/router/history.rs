@@ -0,0 +1,334 @@
+//! Where the router reads and writes the location.
+//!
+//! [`BrowserHistory`] uses the path of the page URL, [`HashHistory`] the fragment,
+//! so it works without server support. [`MemoryHistory`] keeps the stack in memory
+//! and makes routing testable natively.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::web::helpers::dom::WINDOW;
+
+pub trait History {
+	/// Current URL relative to the application, e.g. `/users/1?tab=posts`
+	fn url(&self) -> String;
+
+	/// Identifies the current entry, stays the same when the user comes back to it
+	/// and when the entry is replaced
+	fn key(&self) -> u64;
+
+	fn push(&self, url: &str);
+
+	fn replace(&self, url: &str);
+
+	/// Moves through the stack like `history.go(delta)`
+	fn go(&self, delta: i32);
+
+	/// `func` is called when the URL changes without [`History::push`] or
+	/// [`History::replace`], e.g. when the user presses the back button
+	fn listen(&self, func: Rc<dyn Fn()>);
+}
+
+/// Listens to `popstate` until dropped
+#[derive(Default)]
+struct PopState(RefCell<Option<Closure<dyn Fn(web_sys::Event)>>>);
+
+impl PopState {
+	fn listen(&self, func: Rc<dyn Fn()>) {
+		let closure = Closure::wrap(Box::new(move |_| func()) as Box<dyn Fn(web_sys::Event)>);
+		WINDOW.with(|w| {
+			w.add_event_listener_with_callback("popstate", closure.as_ref().unchecked_ref())
+				.unwrap()
+		});
+
+		if let Some(prev) = self.0.replace(Some(closure)) {
+			Self::remove(&prev)
+		}
+	}
+
+	fn remove(closure: &Closure<dyn Fn(web_sys::Event)>) {
+		WINDOW.with(|w| {
+			w.remove_event_listener_with_callback("popstate", closure.as_ref().unchecked_ref())
+				.unwrap()
+		});
+	}
+}
+
+impl Drop for PopState {
+	fn drop(&mut self) {
+		if let Some(closure) = self.0.get_mut().take() {
+			Self::remove(&closure)
+		}
+	}
+}
+
+fn browser_history() -> web_sys::History {
+	WINDOW.with(|w| w.history().unwrap())
+}
+
+fn browser_location() -> web_sys::Location {
+	WINDOW.with(|w| w.location())
+}
+
+thread_local! {
+	// Entries of the previous page loads may be restored, so keys start from the time
+	static NEXT_KEY: Cell<u64> = Cell::new(js_sys::Date::now() as u64);
+}
+
+/// Keys are stored in the state of the browser history entries
+fn browser_key() -> u64 {
+	let history = browser_history();
+	if let Some(key) = history.state().ok().and_then(|state| state.as_f64()) {
+		return key as u64;
+	}
+
+	// Entries created by the browser, e.g. the first one
+	let key = NEXT_KEY.with(|k| k.replace(k.get() + 1));
+	history
+		.replace_state(&JsValue::from_f64(key as f64), "")
+		.unwrap();
+	key
+}
+
+fn browser_push(url: &str) {
+	let key = NEXT_KEY.with(|k| k.replace(k.get() + 1));
+	browser_history()
+		.push_state_with_url(&JsValue::from_f64(key as f64), "", Some(url))
+		.unwrap()
+}
+
+fn browser_replace(url: &str) {
+	let key = browser_key();
+	browser_history()
+		.replace_state_with_url(&JsValue::from_f64(key as f64), "", Some(url))
+		.unwrap()
+}
+
+/// Path, query and fragment of the page URL
+#[derive(Default)]
+pub struct BrowserHistory {
+	pop_state: PopState,
+}
+
+impl BrowserHistory {
+	pub fn new() -> Self {
+		Default::default()
+	}
+}
+
+impl History for BrowserHistory {
+	fn url(&self) -> String {
+		let location = browser_location();
+		format!(
+			"{}{}{}",
+			location.pathname().unwrap(),
+			location.search().unwrap(),
+			location.hash().unwrap()
+		)
+	}
+
+	fn key(&self) -> u64 {
+		browser_key()
+	}
+
+	fn push(&self, url: &str) {
+		browser_push(url)
+	}
+
+	fn replace(&self, url: &str) {
+		browser_replace(url)
+	}
+
+	fn go(&self, delta: i32) {
+		browser_history().go_with_delta(delta).unwrap()
+	}
+
+	fn listen(&self, func: Rc<dyn Fn()>) {
+		self.pop_state.listen(func)
+	}
+}
+
+/// URL stored in the fragment, e.g. `/index.html#/users/1`
+#[derive(Default)]
+pub struct HashHistory {
+	pop_state: PopState,
+}
+
+impl HashHistory {
+	pub fn new() -> Self {
+		Default::default()
+	}
+}
+
+impl History for HashHistory {
+	fn url(&self) -> String {
+		let hash = browser_location().hash().unwrap();
+		match hash.strip_prefix('#') {
+			Some(url) if !url.is_empty() => url.to_owned(),
+			_ => String::from("/"),
+		}
+	}
+
+	fn key(&self) -> u64 {
+		browser_key()
+	}
+
+	fn push(&self, url: &str) {
+		browser_push(&format!("#{}", url))
+	}
+
+	fn replace(&self, url: &str) {
+		browser_replace(&format!("#{}", url))
+	}
+
+	fn go(&self, delta: i32) {
+		browser_history().go_with_delta(delta).unwrap()
+	}
+
+	// `popstate` also fires when the user edits the fragment
+	fn listen(&self, func: Rc<dyn Fn()>) {
+		self.pop_state.listen(func)
+	}
+}
+
+#[derive(Default)]
+struct MemoryHistoryInner {
+	/// URLs and keys
+	entries: RefCell<Vec<(String, u64)>>,
+	index: Cell<usize>,
+	next_key: Cell<u64>,
+	listener: RefCell<Option<Rc<dyn Fn()>>>,
+}
+
+impl MemoryHistoryInner {
+	fn next_key(&self) -> u64 {
+		self.next_key.replace(self.next_key.get() + 1)
+	}
+}
+
+/// History stack in memory, clones share the stack
+#[derive(Clone, Default)]
+pub struct MemoryHistory(Rc<MemoryHistoryInner>);
+
+impl MemoryHistory {
+	pub fn new(url: &str) -> Self {
+		let history = Self::default();
+		history.push(url);
+		history
+	}
+
+	/// URLs of the stack, the entries after the current one are kept until the next push
+	pub fn entries(&self) -> Vec<String> {
+		self.0
+			.entries
+			.borrow()
+			.iter()
+			.map(|(url, _)| url.clone())
+			.collect()
+	}
+
+	pub fn index(&self) -> usize {
+		self.0.index.get()
+	}
+}
+
+impl History for MemoryHistory {
+	fn url(&self) -> String {
+		self.0
+			.entries
+			.borrow()
+			.get(self.0.index.get())
+			.map(|(url, _)| url.clone())
+			.unwrap_or_else(|| String::from("/"))
+	}
+
+	fn key(&self) -> u64 {
+		self.0
+			.entries
+			.borrow()
+			.get(self.0.index.get())
+			.map_or(0, |(_, key)| *key)
+	}
+
+	fn push(&self, url: &str) {
+		let mut entries = self.0.entries.borrow_mut();
+		let index = if entries.is_empty() {
+			0
+		} else {
+			self.0.index.get() + 1
+		};
+
+		entries.truncate(index);
+		entries.push((url.to_owned(), self.0.next_key()));
+		self.0.index.set(index);
+	}
+
+	fn replace(&self, url: &str) {
+		let mut entries = self.0.entries.borrow_mut();
+		match entries.get_mut(self.0.index.get()) {
+			Some((entry, _)) => *entry = url.to_owned(),
+			None => entries.push((url.to_owned(), self.0.next_key())),
+		}
+	}
+
+	fn go(&self, delta: i32) {
+		let len = self.0.entries.borrow().len() as i64;
+		let index = self.0.index.get() as i64 + delta as i64;
+		if delta == 0 || index < 0 || index >= len {
+			return;
+		}
+
+		self.0.index.set(index as usize);
+
+		// Cloned to let the listener navigate
+		let listener = self.0.listener.borrow().clone();
+		if let Some(listener) = listener {
+			listener()
+		}
+	}
+
+	fn listen(&self, func: Rc<dyn Fn()>) {
+		self.0.listener.replace(Some(func));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+	use std::rc::Rc;
+
+	use super::{History, MemoryHistory};
+
+	#[test]
+	fn test_memory_history() {
+		let history = MemoryHistory::new("/");
+		let pops = Rc::new(Cell::new(0));
+		history.listen({
+			let pops = pops.clone();
+			Rc::new(move || pops.set(pops.get() + 1))
+		});
+
+		history.push("/a");
+		history.push("/b");
+		history.go(-2);
+		assert_eq!(history.url(), "/");
+		assert_eq!(pops.get(), 1);
+
+		history.go(-1);
+		assert_eq!(pops.get(), 1);
+
+		history.push("/c");
+		assert_eq!(history.entries(), vec!["/", "/c"]);
+
+		let key = history.key();
+		history.replace("/d");
+		assert_eq!(history.url(), "/d");
+		assert_eq!(history.key(), key);
+
+		history.go(-1);
+		assert_ne!(history.key(), key);
+	}
+}
@@ -0,0 +1,240 @@
+//! Client side routing.
+//!
+//! [`Router`] keeps the current [`Location`] in sync with a [`History`] backend,
+//! [`router_view`] renders the view of the matched [route](route::route) and
+//! [`outlet`] renders the nested routes inside of the parent view:
+//!
+//! ```ignore
+//! let router = Router::new(BrowserHistory::new());
+//! router_view(router, [
+//!     route("/", |_| home()),
+//!     redirect("/u/:id", "/users/:id"),
+//!     route("/users/:id", |params| user(params.parse("id"))).nest([
+//!         route("/", |_| profile()),
+//!         route("/posts", |_| posts()),
+//!     ]),
+//!     route("*", |_| not_found()),
+//! ])
+//! ```
+//!
+//! Components under the view get the router with `cx.env::<Rc<Router>>()`, and the
+//! location with `cx.env::<Var<Location>>()`, which re-renders reactive components
+//! on navigation. [`Navigate`](crate::web::routing::Navigate) actions dispatched below
+//! the view are turned into [`Router::navigate`] calls.
+//!
+//! Routes can also be described by an enum, see [`routable`], and rendered on the
+//! server, see [`server`]. Scroll and focus after navigation are managed by [`scroll`].
+
+use std::cell::{Cell, RefCell};
+use std::fmt::Display;
+use std::rc::{Rc, Weak};
+
+use observe::{batch, Var};
+
+pub mod history;
+pub mod routable;
+pub mod route;
+pub mod scroll;
+pub mod server;
+mod view;
+
+pub use skima_rsx::Routable;
+
+pub use self::history::{BrowserHistory, HashHistory, History, MemoryHistory};
+pub use self::routable::{route_link, Routable};
+pub use self::route::{not_found, redirect, route, Params, Route};
+pub use self::scroll::{NavigationKind, ScrollRestoration, ScrollTarget, WindowScroll};
+pub use self::server::{render_route, stream_route, RouteResponse};
+pub use self::view::{outlet, router_view, Outlet, RouterView, StaticRouterView};
+
+/// URL split into parts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Location {
+	pub path: String,
+	/// Without the leading `?`
+	pub query: String,
+	/// Without the leading `#`
+	pub hash: String,
+}
+
+impl Location {
+	pub fn parse(url: &str) -> Self {
+		let (url, hash) = url.split_once('#').unwrap_or((url, ""));
+		let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+		Location {
+			path: if path.is_empty() {
+				String::from("/")
+			} else {
+				path.to_owned()
+			},
+			query: query.to_owned(),
+			hash: hash.to_owned(),
+		}
+	}
+
+	/// Value of a query parameter, without decoding
+	pub fn query_param(&self, name: &str) -> Option<&str> {
+		self.query
+			.split('&')
+			.filter_map(|pair| pair.split_once('=').or(Some((pair, ""))))
+			.find(|(key, _)| *key == name)
+			.map(|(_, value)| value)
+	}
+}
+
+impl Display for Location {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.path)?;
+		if !self.query.is_empty() {
+			write!(f, "?{}", self.query)?;
+		}
+		if !self.hash.is_empty() {
+			write!(f, "#{}", self.hash)?;
+		}
+		Ok(())
+	}
+}
+
+pub struct Router {
+	history: Box<dyn History>,
+	current: RefCell<Location>,
+	/// Key of the current history entry
+	key: Cell<u64>,
+	location: Var<Location>,
+	subscribers: RefCell<Vec<Weak<dyn Fn()>>>,
+	scroll: RefCell<Option<ScrollRestoration>>,
+}
+
+impl Router {
+	pub fn new(history: impl History + 'static) -> Rc<Self> {
+		let location = Location::parse(&history.url());
+
+		Rc::new_cyclic(|this: &Weak<Router>| {
+			history.listen(Rc::new({
+				let this = this.clone();
+				move || {
+					if let Some(router) = this.upgrade() {
+						router.sync(NavigationKind::Pop)
+					}
+				}
+			}));
+
+			Router {
+				key: Cell::new(history.key()),
+				history: Box::new(history),
+				current: RefCell::new(location.clone()),
+				location: Var::new(location),
+				subscribers: Default::default(),
+				scroll: Default::default(),
+			}
+		})
+	}
+
+	/// Current location, reading it from a reactive component subscribes to changes
+	pub fn location(&self) -> Var<Location> {
+		self.location.clone()
+	}
+
+	/// Current location without subscribing to changes
+	pub fn current(&self) -> Location {
+		self.current.borrow().clone()
+	}
+
+	/// Adds a history entry
+	pub fn navigate(&self, url: &str) {
+		self.history.push(url);
+		self.sync(NavigationKind::Push)
+	}
+
+	/// Replaces the current history entry
+	pub fn replace(&self, url: &str) {
+		self.history.replace(url);
+		self.sync(NavigationKind::Replace)
+	}
+
+	/// Navigates to a typed route
+	pub fn navigate_to(&self, route: &impl Routable) {
+		self.navigate(&route.to_url())
+	}
+
+	/// Current location as a typed route, `None` if no route matches it
+	pub fn route<R: Routable>(&self) -> Option<R> {
+		R::from_url(&self.current().to_string())
+	}
+
+	pub fn back(&self) {
+		self.history.go(-1)
+	}
+
+	pub fn forward(&self) {
+		self.history.go(1)
+	}
+
+	/// `func` is called after every location change while the `Rc` is alive
+	pub fn subscribe(&self, func: &Rc<dyn Fn()>) {
+		self.subscribers.borrow_mut().push(Rc::downgrade(func))
+	}
+
+	/// Restores the scroll offsets and moves the focus on navigation
+	pub fn set_scroll(&self, scroll: ScrollRestoration) {
+		self.scroll.replace(Some(scroll));
+	}
+
+	fn sync(&self, kind: NavigationKind) {
+		let location = Location::parse(&self.history.url());
+		let key = self.history.key();
+		let prev_key = self.key.replace(key);
+		if *self.current.borrow() == location {
+			return;
+		}
+
+		// Replaced entries start from the top, so only the offsets of the left ones matter
+		if kind != NavigationKind::Replace {
+			if let Some(scroll) = &*self.scroll.borrow() {
+				scroll.leave(prev_key)
+			}
+		}
+
+		self.current.replace(location.clone());
+		batch(|| self.location.set(location.clone()));
+
+		// Subscribers may navigate or subscribe, so we iterate over a copy
+		let subscribers = {
+			let mut subscribers = self.subscribers.borrow_mut();
+			subscribers.retain(|s| s.strong_count() > 0);
+			subscribers.clone()
+		};
+
+		for subscriber in subscribers {
+			if let Some(subscriber) = subscriber.upgrade() {
+				subscriber()
+			}
+		}
+
+		// A redirect of a subscriber has already entered its own location
+		if *self.current.borrow() != location {
+			return;
+		}
+
+		if let Some(scroll) = &*self.scroll.borrow() {
+			scroll.enter(kind, key, &location)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Location;
+
+	#[test]
+	fn test_location() {
+		let location = Location::parse("/users/1?tab=posts&page=2#top");
+		assert_eq!(location.path, "/users/1");
+		assert_eq!(location.query_param("page"), Some("2"));
+		assert_eq!(location.hash, "top");
+		assert_eq!(location.to_string(), "/users/1?tab=posts&page=2#top");
+
+		assert_eq!(Location::parse("?a").path, "/");
+	}
+}
@@ -0,0 +1,202 @@
+//! Routes as Rust types.
+//!
+//! `#[derive(Routable)]` maps the variants of an enum to URLs. Named fields are
+//! taken from the `:name` and `*name` segments of the pattern, the other fields
+//! from the query string, where `Option` fields may be missing:
+//!
+//! ```ignore
+//! #[derive(Routable)]
+//! enum AppRoute {
+//!     #[route("/")]
+//!     Home,
+//!     #[route("/todos/:id")]
+//!     Todo { id: Uuid },
+//!     #[route("/search")]
+//!     Search { q: String, page: Option<u32> },
+//!     #[route("/files/*path")]
+//!     File { path: String },
+//! }
+//!
+//! route_link(&AppRoute::Todo { id }, "Open")
+//! ```
+//!
+//! Field values are formatted with `Display` and parsed with `FromStr`. Path
+//! parameters can't be empty, `to_url` panics instead of building the URL of
+//! another route.
+
+use std::fmt::Write;
+
+use crate::web::attr::{attr, Attr};
+use crate::web::html::a;
+use crate::web::tag::Tag;
+use crate::web::HtmlBackend;
+use crate::Markup;
+
+pub trait Routable: Sized {
+	/// Path and query, e.g. `/search?q=rust&page=2`.
+	/// Panics if a path parameter is empty.
+	fn to_url(&self) -> String;
+
+	/// `None` if no variant matches
+	fn from_url(url: &str) -> Option<Self>;
+}
+
+/// `<a>` with the URL of the route, clicks are intercepted by
+/// [`use_history`](crate::web::routing::use_history)
+pub fn route_link<R, M, B>(route: &R, markup: M) -> Tag<(Attr<&'static str, String>, M), B, 1>
+where
+	R: Routable,
+	M: Markup<B>,
+	B: HtmlBackend,
+	Tag<(Attr<&'static str, String>, M), B, 1>: Markup<B>,
+{
+	a((attr("href", route.to_url()), markup))
+}
+
+/// URL split into decoded path segments and query pairs, used by the derived parser
+#[doc(hidden)]
+pub struct Url {
+	pub segments: Vec<String>,
+	pub query: Vec<(String, String)>,
+}
+
+impl Url {
+	pub fn parse(url: &str) -> Option<Self> {
+		let url = url.split('#').next().unwrap_or("");
+		let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+		let segments = path
+			.split('/')
+			.filter(|s| !s.is_empty())
+			.map(decode)
+			.collect::<Option<Vec<_>>>()?;
+
+		let query = query
+			.split('&')
+			.filter(|pair| !pair.is_empty())
+			.map(|pair| {
+				let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+				Some((decode_query(name)?, decode_query(value)?))
+			})
+			.collect::<Option<Vec<_>>>()?;
+
+		Some(Url { segments, query })
+	}
+
+	pub fn query(&self, name: &str) -> Option<&str> {
+		self.query
+			.iter()
+			.find(|(n, _)| n == name)
+			.map(|(_, value)| value.as_str())
+	}
+}
+
+/// Builds the URL in the derived formatter
+#[doc(hidden)]
+#[derive(Default)]
+pub struct UrlBuilder {
+	path: String,
+	query: String,
+}
+
+impl UrlBuilder {
+	pub fn segment(&mut self, segment: &str) {
+		self.path.push('/');
+		self.path.push_str(segment);
+	}
+
+	pub fn param(&mut self, value: &impl std::fmt::Display) {
+		let value = value.to_string();
+		// `/todos//edit` would be parsed as `/todos/edit`
+		assert!(!value.is_empty(), "Path parameters can't be empty");
+
+		self.path.push('/');
+		encode(&mut self.path, &value, false);
+	}
+
+	/// Rest of the path, slashes are kept
+	pub fn rest(&mut self, value: &impl std::fmt::Display) {
+		for segment in value.to_string().split('/').filter(|s| !s.is_empty()) {
+			self.path.push('/');
+			encode(&mut self.path, segment, false);
+		}
+	}
+
+	pub fn query(&mut self, name: &str, value: &impl std::fmt::Display) {
+		self.query
+			.push(if self.query.is_empty() { '?' } else { '&' });
+		encode(&mut self.query, name, true);
+		self.query.push('=');
+		encode(&mut self.query, &value.to_string(), true);
+	}
+
+	pub fn finish(mut self) -> String {
+		if self.path.is_empty() {
+			self.path.push('/');
+		}
+
+		self.path.push_str(&self.query);
+		self.path
+	}
+}
+
+fn encode(out: &mut String, value: &str, query: bool) {
+	for byte in value.bytes() {
+		match byte {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+				out.push(byte as char)
+			}
+			b' ' if query => out.push('+'),
+			_ => write!(out, "%{:02X}", byte).unwrap(),
+		}
+	}
+}
+
+fn decode(value: &str) -> Option<String> {
+	let bytes = value.as_bytes();
+	let mut out = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'%' {
+			let hex = value.get(i + 1..i + 3)?;
+			out.push(u8::from_str_radix(hex, 16).ok()?);
+			i += 3;
+		} else {
+			out.push(bytes[i]);
+			i += 1;
+		}
+	}
+
+	String::from_utf8(out).ok()
+}
+
+fn decode_query(value: &str) -> Option<String> {
+	decode(&value.replace('+', " "))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Url, UrlBuilder};
+
+	#[test]
+	fn test_url_round_trip() {
+		let mut builder = UrlBuilder::default();
+		builder.segment("files");
+		builder.rest(&"a b/c");
+		builder.query("q", &"x&y z");
+		let url = builder.finish();
+		assert_eq!(url, "/files/a%20b/c?q=x%26y+z");
+
+		let parsed = Url::parse(&url).unwrap();
+		assert_eq!(parsed.segments, vec!["files", "a b", "c"]);
+		assert_eq!(parsed.query("q"), Some("x&y z"));
+	}
+
+	#[test]
+	#[should_panic(expected = "Path parameters can't be empty")]
+	fn test_empty_param() {
+		let mut builder = UrlBuilder::default();
+		builder.segment("todos");
+		builder.param(&"");
+	}
+}
@@ -0,0 +1,323 @@
+//! Route table and path matching.
+//!
+//! Patterns are made of `/`-separated segments:
+//!
+//! * `users` matches the segment literally
+//! * `:id` matches any segment and captures it as the `id` parameter
+//! * `*path` matches the rest of the path and captures it as `path`, `*` alone
+//!   matches without capturing
+//!
+//! Routes with children match a prefix of the path and pass the rest to the children.
+
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::tree::Tree;
+use crate::{AnyMarkup, Backend, Markup};
+
+/// Parameters captured by the matched routes, including the ones of the parents
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params(Vec<(&'static str, String)>);
+
+impl Params {
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.0
+			.iter()
+			.rev()
+			.find(|(n, _)| *n == name)
+			.map(|(_, value)| value.as_str())
+	}
+
+	/// Parses the parameter, `None` if it's missing or invalid
+	pub fn parse<T: FromStr>(&self, name: &str) -> Option<T> {
+		self.get(name).and_then(|value| value.parse().ok())
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+		self.0.iter().map(|(name, value)| (*name, value.as_str()))
+	}
+
+	fn extend(&mut self, other: Params) {
+		self.0.extend(other.0)
+	}
+}
+
+/// Markup of a view. Unlike [`AnyMarkup`] it may borrow, e.g. the bump of
+/// [`StaticHtml`](crate::web::ssr::StaticHtml), in which case it's only rendered once.
+pub(crate) trait ViewMarkup<B: Backend> {
+	fn render(&mut self, tree: &Tree<B>);
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool);
+
+	/// Views of interactive routers are diffed as [`AnyMarkup`]
+	fn into_any(self: Box<Self>) -> Box<dyn AnyMarkup<B>>
+	where
+		Self: 'static,
+		B: 'static;
+}
+
+impl<B: Backend, M: Markup<B>> ViewMarkup<B> for M {
+	fn render(&mut self, tree: &Tree<B>) {
+		Markup::render(self, tree)
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		Markup::drop(self, tree, should_unmount)
+	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn AnyMarkup<B>>
+	where
+		Self: 'static,
+		B: 'static,
+	{
+		self
+	}
+}
+
+pub(crate) type View<'a, B> = Rc<dyn Fn(&Params) -> Box<dyn ViewMarkup<B> + 'a> + 'a>;
+
+enum Target<'a, B: Backend> {
+	View(View<'a, B>),
+	Redirect(&'static str),
+}
+
+/// Entry of the route table, see [`route`], [`redirect`] and [`not_found`].
+/// Views of a [`router_view`](super::router_view) live for `'static`,
+/// the ones rendered on the [server](super::server) may borrow the backend.
+pub struct Route<'a, B: Backend> {
+	pattern: &'static str,
+	target: Target<'a, B>,
+	children: Vec<Route<'a, B>>,
+	not_found: bool,
+}
+
+/// Renders `view` when the path matches the `pattern`
+pub fn route<'a, B, M, F>(pattern: &'static str, view: F) -> Route<'a, B>
+where
+	B: Backend + 'a,
+	M: Markup<B> + 'a,
+	F: Fn(&Params) -> M + 'a,
+{
+	Route {
+		pattern,
+		target: Target::View(Rc::new(
+			move |params: &Params| -> Box<dyn ViewMarkup<B> + 'a> { Box::new(view(params)) },
+		)),
+		children: Vec::new(),
+		not_found: false,
+	}
+}
+
+/// Renders `view` for any path, the [server](super::server) responds with
+/// [`RouteResponse::NotFound`](super::RouteResponse::NotFound) when it's matched
+pub fn not_found<'a, B, M, F>(view: F) -> Route<'a, B>
+where
+	B: Backend + 'a,
+	M: Markup<B> + 'a,
+	F: Fn(&Params) -> M + 'a,
+{
+	Route {
+		not_found: true,
+		..route("*", view)
+	}
+}
+
+/// Replaces the location when the path matches the `pattern`.
+/// Parameters of the pattern can be used in the target, e.g. `redirect("/u/:id", "/users/:id")`.
+pub fn redirect<'a, B: Backend>(pattern: &'static str, to: &'static str) -> Route<'a, B> {
+	Route {
+		pattern,
+		target: Target::Redirect(to),
+		children: Vec::new(),
+		not_found: false,
+	}
+}
+
+impl<'a, B: Backend> Route<'a, B> {
+	/// Nested routes, rendered by the [`outlet`](super::outlet) of the view
+	pub fn nest(mut self, children: impl IntoIterator<Item = Route<'a, B>>) -> Self {
+		self.children.extend(children);
+		self
+	}
+}
+
+/// Route of a [`Resolved::Views`] chain
+pub(crate) struct Matched<'a, B: Backend> {
+	/// Index of the route among its siblings
+	pub index: usize,
+	pub params: Params,
+	pub view: View<'a, B>,
+	pub not_found: bool,
+}
+
+pub(crate) enum Resolved<'a, B: Backend> {
+	/// Matched routes from the top level to the innermost one
+	Views(Vec<Matched<'a, B>>),
+	Redirect(String),
+	NotFound,
+}
+
+pub(crate) fn resolve<'a, B: Backend>(routes: &[Route<'a, B>], path: &str) -> Resolved<'a, B> {
+	resolve_in(routes, path, &Params::default()).unwrap_or(Resolved::NotFound)
+}
+
+fn resolve_in<'a, B: Backend>(
+	routes: &[Route<'a, B>],
+	path: &str,
+	parent: &Params,
+) -> Option<Resolved<'a, B>> {
+	for (index, route) in routes.iter().enumerate() {
+		let Some((captured, rest)) = match_path(route.pattern, path) else {
+			continue;
+		};
+
+		let mut params = parent.clone();
+		params.extend(captured);
+
+		let view = match &route.target {
+			Target::Redirect(to) if rest.is_empty() => {
+				return Some(Resolved::Redirect(fill(to, &params)))
+			}
+			Target::Redirect(_) => continue,
+			Target::View(view) => view.clone(),
+		};
+
+		let matched = Matched {
+			index,
+			params: params.clone(),
+			view,
+			not_found: route.not_found,
+		};
+
+		match resolve_in(&route.children, rest, &params) {
+			Some(Resolved::Views(mut chain)) => {
+				chain.insert(0, matched);
+				return Some(Resolved::Views(chain));
+			}
+			Some(redirect @ Resolved::Redirect(_)) => return Some(redirect),
+			_ if rest.is_empty() => return Some(Resolved::Views(vec![matched])),
+			_ => continue,
+		}
+	}
+
+	None
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+	path.split('/').filter(|s| !s.is_empty())
+}
+
+/// Matches the beginning of the `path`, returns the captured parameters and the rest
+pub fn match_path<'p>(pattern: &'static str, path: &'p str) -> Option<(Params, &'p str)> {
+	let mut params = Params::default();
+	let mut rest = path;
+
+	for segment in segments(pattern) {
+		let trimmed = rest.trim_start_matches('/');
+
+		if let Some(name) = segment.strip_prefix('*') {
+			if !name.is_empty() {
+				params.0.push((name, trimmed.to_owned()));
+			}
+			return Some((params, ""));
+		}
+
+		let (head, tail) = trimmed.split_at(trimmed.find('/').unwrap_or(trimmed.len()));
+		if head.is_empty() {
+			return None;
+		}
+
+		if let Some(name) = segment.strip_prefix(':') {
+			params.0.push((name, head.to_owned()));
+		} else if segment != head {
+			return None;
+		}
+
+		rest = tail;
+	}
+
+	let rest = if segments(rest).next().is_none() {
+		""
+	} else {
+		rest
+	};
+
+	Some((params, rest))
+}
+
+/// Substitutes `:name` segments of the pattern
+fn fill(pattern: &str, params: &Params) -> String {
+	let mut path = String::new();
+	for segment in segments(pattern) {
+		path.push('/');
+		match segment.strip_prefix(':').and_then(|name| params.get(name)) {
+			Some(value) => path.push_str(value),
+			None => path.push_str(segment),
+		}
+	}
+
+	if path.is_empty() {
+		path.push('/');
+	}
+
+	path
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::web::mock::MockDom;
+
+	#[test]
+	fn test_match_path() {
+		let (params, rest) = match_path("/users/:id", "/users/42/posts").unwrap();
+		assert_eq!(params.get("id"), Some("42"));
+		assert_eq!(params.parse::<u32>("id"), Some(42));
+		assert_eq!(rest, "/posts");
+
+		let (params, rest) = match_path("/files/*path", "/files/a/b.txt").unwrap();
+		assert_eq!(params.get("path"), Some("a/b.txt"));
+		assert_eq!(rest, "");
+
+		assert_eq!(match_path("/", "/anything").unwrap().1, "/anything");
+		assert!(match_path("/users/:id", "/users").is_none());
+		assert!(match_path("/users", "/teams").is_none());
+	}
+
+	fn routes() -> Vec<Route<'static, MockDom>> {
+		vec![
+			route("/", |_| "home"),
+			redirect("/u/:id", "/users/:id"),
+			route("/users/:id", |_| "user")
+				.nest([route("/", |_| "profile"), route("/posts/:post", |_| "post")]),
+			route("*", |_| "not found"),
+		]
+	}
+
+	fn chain(path: &str) -> Vec<(usize, Params)> {
+		match resolve(&routes(), path) {
+			Resolved::Views(chain) => chain.into_iter().map(|m| (m.index, m.params)).collect(),
+			_ => panic!("{} is not resolved to views", path),
+		}
+	}
+
+	#[test]
+	fn test_resolve() {
+		assert_eq!(chain("/")[0].0, 0);
+		assert_eq!(
+			chain("/users/1").iter().map(|m| m.0).collect::<Vec<_>>(),
+			vec![2, 0]
+		);
+
+		let post = chain("/users/1/posts/2");
+		assert_eq!(post.iter().map(|m| m.0).collect::<Vec<_>>(), vec![2, 1]);
+		assert_eq!(post[1].1.get("id"), Some("1"));
+		assert_eq!(post[1].1.get("post"), Some("2"));
+
+		assert_eq!(chain("/teams")[0].0, 3);
+		assert!(matches!(
+			resolve(&routes(), "/u/7"),
+			Resolved::Redirect(to) if to == "/users/7"
+		));
+	}
+}
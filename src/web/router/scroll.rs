@@ -0,0 +1,145 @@
+//! Scroll and focus management on navigation.
+//!
+//! [`ScrollRestoration`] saves the scroll offset of the history entry the user
+//! leaves and restores it when the user comes back with back/forward. Other
+//! navigations scroll to the element of the `#fragment` or to the top, and move
+//! the focus to a landmark, so that screen readers announce the new page:
+//!
+//! ```ignore
+//! let router = Router::new(BrowserHistory::new());
+//! router.set_scroll(ScrollRestoration::new(WindowScroll::new()).focus("main"));
+//! ```
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use wasm_bindgen::JsCast;
+
+use super::Location;
+use crate::web::helpers::dom::{DOCUMENT, WINDOW};
+
+/// How the location was changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationKind {
+	Push,
+	Replace,
+	/// Back, forward or `go`
+	Pop,
+}
+
+/// Page that is scrolled and focused on navigation
+pub trait ScrollTarget {
+	fn offset(&self) -> (f64, f64);
+
+	fn scroll_to(&self, x: f64, y: f64);
+
+	/// Scrolls the element with the `id` into view, `false` if there is no such element
+	fn scroll_to_id(&self, id: &str) -> bool;
+
+	/// Focuses the first element that matches the selector, `false` if nothing matches
+	fn focus(&self, selector: &str) -> bool;
+}
+
+/// Scrolls the window
+pub struct WindowScroll(());
+
+impl WindowScroll {
+	/// Turns off the scroll restoration of the browser
+	pub fn new() -> Self {
+		WINDOW.with(|w| {
+			w.history()
+				.unwrap()
+				.set_scroll_restoration(web_sys::ScrollRestoration::Manual)
+				.unwrap()
+		});
+
+		WindowScroll(())
+	}
+}
+
+impl Default for WindowScroll {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl ScrollTarget for WindowScroll {
+	fn offset(&self) -> (f64, f64) {
+		WINDOW.with(|w| (w.scroll_x().unwrap(), w.scroll_y().unwrap()))
+	}
+
+	fn scroll_to(&self, x: f64, y: f64) {
+		WINDOW.with(|w| w.scroll_to_with_x_and_y(x, y))
+	}
+
+	fn scroll_to_id(&self, id: &str) -> bool {
+		match DOCUMENT.with(|d| d.get_element_by_id(id)) {
+			Some(element) => {
+				element.scroll_into_view();
+				true
+			}
+			None => false,
+		}
+	}
+
+	fn focus(&self, selector: &str) -> bool {
+		let element = DOCUMENT.with(|d| d.query_selector(selector).ok().flatten());
+		let Some(element) = element.and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok()) else {
+			return false;
+		};
+
+		// Landmarks are not focusable by default
+		if !element.has_attribute("tabindex") {
+			element.set_attribute("tabindex", "-1").unwrap();
+		}
+
+		element.focus().is_ok()
+	}
+}
+
+/// See the [module docs](self)
+pub struct ScrollRestoration {
+	target: Box<dyn ScrollTarget>,
+	focus: Option<Cow<'static, str>>,
+	/// Offsets by the history entry keys
+	offsets: RefCell<HashMap<u64, (f64, f64)>>,
+}
+
+impl ScrollRestoration {
+	pub fn new(target: impl ScrollTarget + 'static) -> Self {
+		ScrollRestoration {
+			target: Box::new(target),
+			focus: None,
+			offsets: Default::default(),
+		}
+	}
+
+	/// Selector of the element to focus after navigation, e.g. `main` or `#content`
+	pub fn focus(mut self, selector: impl Into<Cow<'static, str>>) -> Self {
+		self.focus = Some(selector.into());
+		self
+	}
+
+	/// Called before the views are updated, `key` is the entry being left
+	pub(crate) fn leave(&self, key: u64) {
+		self.offsets.borrow_mut().insert(key, self.target.offset());
+	}
+
+	/// Called after the views are updated, `key` is the new entry
+	pub(crate) fn enter(&self, kind: NavigationKind, key: u64, location: &Location) {
+		let saved = self.offsets.borrow().get(&key).copied();
+
+		match saved {
+			Some((x, y)) if kind == NavigationKind::Pop => self.target.scroll_to(x, y),
+			_ if !location.hash.is_empty() && self.target.scroll_to_id(&location.hash) => {}
+			_ => self.target.scroll_to(0.0, 0.0),
+		}
+
+		if let Some(selector) = &self.focus {
+			if !self.target.focus(selector) {
+				tracing::warn!("No element matches the focus selector {}", selector);
+			}
+		}
+	}
+}
@@ -0,0 +1,110 @@
+//! Routing during server-side rendering.
+//!
+//! The URL of the request is resolved before the render, redirects are reported
+//! as a [`RouteResponse`] instead of HTML. When no route matches, or the matched one
+//! is a [`not_found`](super::not_found) route, the page is rendered as a 404:
+//!
+//! ```ignore
+//! match stream_route(&request.url, routes(), |view| page(view)) {
+//!     RouteResponse::Ok(html) => respond(200, html),
+//!     RouteResponse::Redirect(to) => redirect(to),
+//!     RouteResponse::NotFound(html) => respond(404, html),
+//! }
+//! ```
+//!
+//! The page is rendered for the URL of the request, so a client that creates its
+//! router with [`BrowserHistory`](super::BrowserHistory) starts from the same location
+//! and hydrates the same views. Views are rendered once by a [`StaticRouterView`], so
+//! the route table may borrow, e.g. `Route<'a, StaticHtml<'a>>`.
+
+use super::route::{resolve, Resolved, Route};
+use super::view::{static_router_view, StaticRouterView};
+use super::{MemoryHistory, Router};
+use crate::web::ssr::{render_with_styles, RenderedHtml, StaticHtml};
+use crate::web::stream::{render_to_string, HtmlStream};
+use crate::{Backend, Markup};
+
+/// Outcome of a server-side route render
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteResponse<T> {
+	/// Rendered page of the matched routes
+	Ok(T),
+	/// The URL matched a [redirect](super::redirect), the client should go to the target
+	Redirect(String),
+	/// Rendered page of a [`not_found`](super::not_found) route, the router view
+	/// is empty if no route matched the URL
+	NotFound(T),
+}
+
+impl<T> RouteResponse<T> {
+	/// HTTP status code of the response
+	pub fn status(&self) -> u16 {
+		match self {
+			RouteResponse::Ok(_) => 200,
+			RouteResponse::Redirect(_) => 302,
+			RouteResponse::NotFound(_) => 404,
+		}
+	}
+
+	pub fn map<U>(self, func: impl FnOnce(T) -> U) -> RouteResponse<U> {
+		match self {
+			RouteResponse::Ok(value) => RouteResponse::Ok(func(value)),
+			RouteResponse::Redirect(to) => RouteResponse::Redirect(to),
+			RouteResponse::NotFound(value) => RouteResponse::NotFound(func(value)),
+		}
+	}
+}
+
+/// Resolves the `url` and passes the page to `render` when a view matches.
+/// `app` wraps the router view into the rest of the page.
+pub fn route_response<'a, B, M, T>(
+	url: &str,
+	routes: impl IntoIterator<Item = Route<'a, B>>,
+	app: impl FnOnce(StaticRouterView<'a, B>) -> M,
+	render: impl FnOnce(M) -> T,
+) -> RouteResponse<T>
+where
+	B: Backend,
+	M: Markup<B>,
+{
+	let routes: Vec<_> = routes.into_iter().collect();
+	let router = Router::new(MemoryHistory::new(url));
+
+	match resolve(&routes, &router.current().path) {
+		Resolved::Views(chain) if chain.iter().any(|m| m.not_found) => {
+			RouteResponse::NotFound(render(app(static_router_view(router, chain))))
+		}
+		Resolved::Views(chain) => RouteResponse::Ok(render(app(static_router_view(router, chain)))),
+		Resolved::Redirect(to) => RouteResponse::Redirect(to),
+		Resolved::NotFound => {
+			RouteResponse::NotFound(render(app(static_router_view(router, Vec::new()))))
+		}
+	}
+}
+
+/// Renders the page of the `url` with [`StaticHtml`]
+pub fn render_route<'a, M>(
+	backend: StaticHtml<'a>,
+	url: &str,
+	routes: impl IntoIterator<Item = Route<'a, StaticHtml<'a>>>,
+	app: impl FnOnce(StaticRouterView<'a, StaticHtml<'a>>) -> M,
+) -> RouteResponse<RenderedHtml>
+where
+	M: Markup<StaticHtml<'a>>,
+{
+	route_response(url, routes, app, |markup| {
+		render_with_styles(backend, markup)
+	})
+}
+
+/// Renders the page of the `url` with [`HtmlStream`]
+pub fn stream_route<'a, M>(
+	url: &str,
+	routes: impl IntoIterator<Item = Route<'a, HtmlStream<'a>>>,
+	app: impl FnOnce(StaticRouterView<'a, HtmlStream<'a>>) -> M,
+) -> RouteResponse<String>
+where
+	M: Markup<HtmlStream<'a>>,
+{
+	route_response(url, routes, app, render_to_string)
+}
@@ -0,0 +1,537 @@
+use std::any::TypeId;
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
+
+use observe::Var;
+
+use super::route::{resolve, Matched, Params, Resolved, Route, View, ViewMarkup};
+use super::{Location, Router};
+use crate::action::Action;
+use crate::tree::Tree;
+use crate::web::dispatch::{ActionHandler, ActionResult};
+use crate::web::routing::Navigate;
+use crate::web::Callback;
+use crate::{AnyMarkup, Backend, Markup};
+
+// Stops redirect loops
+const MAX_REDIRECTS: usize = 16;
+
+/// Route table and the matched routes, shared by the view and its outlets
+struct RouterState<B: Backend> {
+	router: Rc<Router>,
+	routes: RefCell<Vec<Route<'static, B>>>,
+	matched: RefCell<Vec<Matched<'static, B>>>,
+	redirects: Cell<usize>,
+	levels: RefCell<Vec<Weak<dyn Fn()>>>,
+	_subscription: Rc<dyn Fn()>,
+}
+
+impl<B: Backend + 'static> RouterState<B> {
+	fn new(router: Rc<Router>, routes: Vec<Route<'static, B>>) -> Rc<Self> {
+		Rc::new_cyclic(|this: &Weak<Self>| {
+			let subscription: Rc<dyn Fn()> = Rc::new({
+				let this = this.clone();
+				move || {
+					if let Some(state) = this.upgrade() {
+						state.resolve();
+						state.notify();
+					}
+				}
+			});
+
+			router.subscribe(&subscription);
+
+			RouterState {
+				router,
+				routes: RefCell::new(routes),
+				matched: Default::default(),
+				redirects: Cell::new(0),
+				levels: Default::default(),
+				_subscription: subscription,
+			}
+		})
+	}
+
+	fn resolve(&self) {
+		let path = self.router.current().path;
+		let resolved = resolve(&self.routes.borrow(), &path);
+
+		match resolved {
+			Resolved::Views(chain) => {
+				self.matched.replace(chain);
+			}
+			Resolved::NotFound => {
+				self.matched.replace(Vec::new());
+			}
+			Resolved::Redirect(to) => {
+				let redirects = self.redirects.get();
+				if redirects >= MAX_REDIRECTS {
+					tracing::warn!("Too many redirects, stopped at {}", to);
+					self.matched.replace(Vec::new());
+					return;
+				}
+
+				// Resolves again through the subscription
+				self.redirects.set(redirects + 1);
+				self.router.replace(&to);
+				self.redirects.set(redirects);
+			}
+		}
+	}
+
+	fn notify(&self) {
+		// Levels unmount and mount outlets while being notified
+		let levels = {
+			let mut levels = self.levels.borrow_mut();
+			levels.retain(|l| l.strong_count() > 0);
+			levels.clone()
+		};
+
+		for level in levels {
+			if let Some(level) = level.upgrade() {
+				level()
+			}
+		}
+	}
+
+	/// Indices of the routes up to `depth`, the parameters and the view
+	fn matched_at(&self, depth: usize) -> Option<(Vec<usize>, Params, View<'static, B>)> {
+		let matched = self.matched.borrow();
+		let route = matched.get(depth)?;
+
+		Some((
+			matched[..=depth].iter().map(|m| m.index).collect(),
+			route.params.clone(),
+			route.view.clone(),
+		))
+	}
+}
+
+/// Level of a view as seen by its outlets, which don't know if the backend is `'static`
+trait LevelHandle {
+	/// Mounts the nested level into the outlet that was just rendered
+	fn mount_outlet(&self);
+
+	fn update(&self, force: bool);
+
+	fn unmount(&self, should_unmount: bool);
+}
+
+/// Set on the tree of a level, outlets below it render the nested level through it
+enum OutletHost {
+	Level(Weak<dyn LevelHandle>),
+	Static(StaticHost),
+}
+
+/// Set on the tree of an outlet, holds the nested level of an interactive router
+#[derive(Default)]
+struct OutletSlot(RefCell<Option<Rc<dyn LevelHandle>>>);
+
+fn outlet_host<B: Backend>(tree: &Tree<B>) -> Option<Rc<OutletHost>> {
+	let mut cursor = tree.parent.clone();
+	while let Some(tree) = cursor {
+		if let Some(host) = tree.data().try_get::<Rc<OutletHost>>() {
+			return Some(host);
+		}
+		cursor = tree.parent.clone();
+	}
+
+	None
+}
+
+/// Outlet under the `tree` that has no nested level yet
+fn empty_slot<B: Backend>(tree: &Tree<B>) -> Option<(Tree<B>, Rc<OutletSlot>)> {
+	for child in tree.children.borrow().iter() {
+		let child = &child.0;
+		match child.data().try_get::<Rc<OutletSlot>>() {
+			Some(slot) if slot.0.borrow().is_none() => return Some((child.clone(), slot)),
+			// Trees under a filled outlet belong to the nested level
+			Some(_) => continue,
+			None => {
+				if let Some(found) = empty_slot(child) {
+					return Some(found);
+				}
+			}
+		}
+	}
+
+	None
+}
+
+/// Renders the view of the route at `depth`
+struct Level<B: Backend> {
+	depth: usize,
+	state: Rc<RouterState<B>>,
+	tree: Tree<B>,
+	current: RefCell<Option<(Vec<usize>, Params)>>,
+	markup: RefCell<Option<Box<dyn AnyMarkup<B>>>>,
+	_subscription: Rc<dyn Fn()>,
+}
+
+impl<B: Backend + 'static> Level<B> {
+	fn mount(tree: &Tree<B>, state: Rc<RouterState<B>>, depth: usize) -> Rc<Self> {
+		let level = Rc::new_cyclic(|this: &Weak<Self>| {
+			let subscription: Rc<dyn Fn()> = Rc::new({
+				let this = this.clone();
+				move || {
+					if let Some(level) = this.upgrade() {
+						level.update(false)
+					}
+				}
+			});
+
+			state.levels.borrow_mut().push(Rc::downgrade(&subscription));
+
+			Level {
+				depth,
+				state,
+				tree: tree.clone(),
+				current: Default::default(),
+				markup: Default::default(),
+				_subscription: subscription,
+			}
+		});
+
+		// Set before the render, so that outlets find their parent
+		let handle: Weak<dyn LevelHandle> = Rc::downgrade(&level);
+		tree.data_mut().set(Rc::new(OutletHost::Level(handle)));
+		level.update(true);
+		level
+	}
+
+	/// `force` re-renders the view even if the route and the parameters are the same
+	fn update(&self, force: bool) {
+		let matched = self.state.matched_at(self.depth);
+		let key = matched
+			.as_ref()
+			.map(|(path, params, _)| (path.clone(), params.clone()));
+
+		let same_route = {
+			let current = self.current.borrow();
+			if !force && *current == key && self.markup.borrow().is_some() {
+				return;
+			}
+
+			current.as_ref().map(|c| &c.0) == key.as_ref().map(|k| &k.0)
+		};
+
+		self.current.replace(key);
+
+		let mut next = match matched {
+			Some((_, params, view)) => view(&params).into_any(),
+			None => Markup::<B>::any(()),
+		};
+
+		let prev = self.markup.borrow_mut().take();
+		match prev {
+			// Same route with other parameters keeps the state of the view
+			Some(mut prev) if same_route => Markup::diff(&mut next, &mut prev, &self.tree),
+			Some(mut prev) => {
+				Markup::drop(&mut prev, &self.tree, true);
+				Markup::render(&mut next, &self.tree);
+			}
+			None => Markup::render(&mut next, &self.tree),
+		}
+
+		self.markup.replace(Some(next));
+	}
+
+	fn unmount(&self, should_unmount: bool) {
+		let markup = self.markup.borrow_mut().take();
+		if let Some(mut markup) = markup {
+			Markup::drop(&mut markup, &self.tree, should_unmount);
+		}
+
+		self.tree.data_mut().remove::<Rc<OutletHost>>();
+	}
+}
+
+impl<B: Backend + 'static> LevelHandle for Level<B> {
+	fn mount_outlet(&self) {
+		if let Some((tree, slot)) = empty_slot(&self.tree) {
+			let level: Rc<dyn LevelHandle> =
+				Level::mount(&tree, self.state.clone(), self.depth + 1);
+			slot.0.replace(Some(level));
+		}
+	}
+
+	fn update(&self, force: bool) {
+		Level::update(self, force)
+	}
+
+	fn unmount(&self, should_unmount: bool) {
+		Level::unmount(self, should_unmount)
+	}
+}
+
+fn capture_key() -> u64 {
+	fxhash::hash64(&TypeId::of::<Router>())
+}
+
+fn navigate_handler(router: &Rc<Router>) -> ActionHandler {
+	let router = Rc::downgrade(router);
+	Callback::new(
+		move |action: Box<dyn Action>| match action.downcast::<Navigate>() {
+			Ok(navigate) => {
+				if let Some(router) = router.upgrade() {
+					router.navigate(&navigate.0)
+				}
+				ActionResult::Stop
+			}
+			Err(action) => ActionResult::Propagate(action),
+		},
+	)
+	.to_dyn()
+}
+
+/// Makes the router available to the components under the view
+fn provide<B: Backend>(tree: &Tree<B>, router: &Rc<Router>) {
+	tree.data_mut().set(router.clone());
+	tree.data_mut().set(router.location());
+	tree.capture
+		.borrow_mut()
+		.insert(capture_key(), navigate_handler(router));
+}
+
+fn unprovide<B: Backend>(tree: &Tree<B>) {
+	tree.data_mut().remove::<Rc<Router>>();
+	tree.data_mut().remove::<Var<Location>>();
+	tree.capture.borrow_mut().remove(&capture_key());
+}
+
+/// See [`router_view`]
+pub struct RouterView<B: Backend> {
+	router: Rc<Router>,
+	routes: Vec<Route<'static, B>>,
+}
+
+/// Renders the view of the route that matches the location of the `router`
+pub fn router_view<B: Backend>(
+	router: Rc<Router>,
+	routes: impl IntoIterator<Item = Route<'static, B>>,
+) -> RouterView<B> {
+	RouterView {
+		router,
+		routes: routes.into_iter().collect(),
+	}
+}
+
+impl<B: Backend + 'static> Markup<B> for RouterView<B> {
+	fn has_own_node() -> bool {
+		true
+	}
+
+	fn dynamic() -> bool {
+		true
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		#[cfg(debug_assertions)]
+		tree.name.replace(std::borrow::Cow::Borrowed("RouterView"));
+
+		provide(tree, &self.router);
+
+		let state = RouterState::new(self.router.clone(), std::mem::take(&mut self.routes));
+		state.resolve();
+		let level = Level::mount(tree, state, 0);
+		tree.data_mut().set(level);
+	}
+
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		if !Rc::ptr_eq(&self.router, &prev.router) {
+			Markup::<B>::drop(prev, tree, true);
+			self.render(tree);
+			return;
+		}
+
+		// Views of the new routes may capture new values
+		let level = tree.data().get::<Rc<Level<B>>>();
+		level.state.routes.replace(std::mem::take(&mut self.routes));
+		level.state.resolve();
+		level.update(true);
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		let level = tree.data_mut().remove::<Rc<Level<B>>>();
+		level.unmount(should_unmount);
+		unprovide(tree);
+	}
+}
+
+/// Renders the views of the matched routes once, without subscribing to the router.
+/// Unlike [`RouterView`] the views may borrow, e.g. the bump of
+/// [`StaticHtml`](crate::web::ssr::StaticHtml). See [`server`](super::server).
+pub struct StaticRouterView<'a, B: Backend> {
+	router: Rc<Router>,
+	chain: Vec<Matched<'a, B>>,
+	/// Rendered views and their trees, the innermost first
+	rendered: RefCell<Vec<(Tree<B>, Box<dyn ViewMarkup<B> + 'a>)>>,
+}
+
+pub(crate) fn static_router_view<'a, B: Backend>(
+	router: Rc<Router>,
+	chain: Vec<Matched<'a, B>>,
+) -> StaticRouterView<'a, B> {
+	StaticRouterView {
+		router,
+		chain,
+		rendered: Default::default(),
+	}
+}
+
+/// Lets outlets render the nested views of a [`StaticRouterView`] while it renders
+struct StaticHost {
+	view: Rc<Cell<Option<*const ()>>>,
+	depth: usize,
+	backend: &'static str,
+}
+
+impl StaticHost {
+	/// # Safety
+	///
+	/// `B` should be the backend of the view, which holds for the trees it rendered
+	unsafe fn render<B: Backend>(&self, tree: &Tree<B>) {
+		assert_eq!(
+			self.backend,
+			std::any::type_name::<B>(),
+			"Outlet is rendered by a static router of another backend"
+		);
+
+		match self.view.get() {
+			// SAFETY: the pointer is only set while `StaticRouterView::render` runs and is
+			// cleared when it returns or panics, so the view and everything it borrows
+			// for `'a` are alive. The backend is checked above, the lifetimes of `B` don't
+			// change the layout of the view.
+			Some(view) => {
+				let view = &*(view as *const StaticRouterView<'_, B>);
+				view.render_level(tree, self.depth + 1, &self.view)
+			}
+			None => tracing::warn!("Outlet of a static router is rendered after the router"),
+		}
+	}
+}
+
+impl<'a, B: Backend> StaticRouterView<'a, B> {
+	fn render_level(&self, tree: &Tree<B>, depth: usize, view: &Rc<Cell<Option<*const ()>>>) {
+		let Some(matched) = self.chain.get(depth) else {
+			return;
+		};
+
+		tree.data_mut().set(Rc::new(OutletHost::Static(StaticHost {
+			view: view.clone(),
+			depth,
+			backend: std::any::type_name::<B>(),
+		})));
+
+		let mut markup = (matched.view)(&matched.params);
+		markup.render(tree);
+		self.rendered.borrow_mut().push((tree.clone(), markup));
+	}
+}
+
+impl<'a, B: Backend> Markup<B> for StaticRouterView<'a, B> {
+	fn has_own_node() -> bool {
+		true
+	}
+
+	fn dynamic() -> bool {
+		true
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		#[cfg(debug_assertions)]
+		tree.name.replace(std::borrow::Cow::Borrowed("RouterView"));
+
+		provide(tree, &self.router);
+
+		// Cleared even if the render panics
+		struct Scope(Rc<Cell<Option<*const ()>>>);
+
+		impl Drop for Scope {
+			fn drop(&mut self) {
+				self.0.set(None)
+			}
+		}
+
+		let this: &Self = self;
+		let scope = Scope(Rc::new(Cell::new(Some(this as *const Self as *const ()))));
+		this.render_level(tree, 0, &scope.0);
+	}
+
+	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
+		Markup::<B>::drop(prev, tree, true);
+		self.render(tree);
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		// Nodes of the nested views are removed with the parent ones
+		let rendered = self.rendered.take();
+		let count = rendered.len();
+		for (index, (level, mut markup)) in rendered.into_iter().enumerate() {
+			markup.drop(&level, should_unmount && index + 1 == count);
+			level.data_mut().remove::<Rc<OutletHost>>();
+		}
+
+		unprovide(tree);
+	}
+}
+
+/// Renders the nested route in the view of the parent route
+pub struct Outlet<B>(PhantomData<B>);
+
+pub fn outlet<B: Backend>() -> Outlet<B> {
+	Outlet(PhantomData)
+}
+
+impl<B: Backend> Outlet<B> {
+	fn level(tree: &Tree<B>) -> Option<Rc<dyn LevelHandle>> {
+		tree.data()
+			.try_get::<Rc<OutletSlot>>()
+			.and_then(|slot| slot.0.borrow().clone())
+	}
+}
+
+impl<B: Backend> Markup<B> for Outlet<B> {
+	fn has_own_node() -> bool {
+		true
+	}
+
+	fn dynamic() -> bool {
+		true
+	}
+
+	fn render(&mut self, tree: &Tree<B>) {
+		#[cfg(debug_assertions)]
+		tree.name.replace(std::borrow::Cow::Borrowed("Outlet"));
+
+		tree.data_mut().set(Rc::new(OutletSlot::default()));
+
+		let host = outlet_host(tree).expect("Outlet should be rendered by a route view");
+		match &*host {
+			OutletHost::Level(level) => {
+				if let Some(level) = level.upgrade() {
+					level.mount_outlet()
+				}
+			}
+			// SAFETY: the host is found on an ancestor `Tree<B>`, and static hosts are only
+			// set by `StaticRouterView<B>` on the trees it renders, so `B` is its backend
+			OutletHost::Static(host) => unsafe { host.render(tree) },
+		}
+	}
+
+	fn diff(&mut self, _prev: &mut Self, tree: &Tree<B>) {
+		// The parent view is re-rendered, so is the nested one
+		if let Some(level) = Self::level(tree) {
+			level.update(true);
+		}
+	}
+
+	fn drop(&mut self, tree: &Tree<B>, should_unmount: bool) {
+		if let Some(level) = Self::level(tree) {
+			level.unmount(should_unmount);
+		}
+
+		tree.data_mut().remove::<Rc<OutletSlot>>();
+	}
+}
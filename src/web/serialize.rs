@@ -0,0 +1,284 @@
+//! HTML serialization of the [`StaticHtml`](super::ssr::StaticHtml) tree.
+//!
+//! Follows the HTML fragment serialization algorithm: text and attribute
+//! values are escaped, void elements have no end tag and the contents
+//! of raw text elements are written as is. Both rules only apply to
+//! elements in the HTML namespace, SVG and MathML elements are always closed.
+
+use std::fmt::{self, Write};
+
+use super::ssr::{StaticElement, StaticNode};
+use super::tag::{element_info, ContentModel, Display, ElementInfo};
+use crate::Namespace;
+
+pub const DOCTYPE: &str = "<!DOCTYPE html>";
+
+fn html_info(namespace: Namespace, tag: &str) -> Option<&'static ElementInfo> {
+	if namespace == Namespace::Html {
+		element_info(tag)
+	} else {
+		None
+	}
+}
+
+pub fn is_void(namespace: Namespace, tag: &str) -> bool {
+	html_info(namespace, tag).is_some_and(|i| i.content == ContentModel::Void)
+}
+
+pub fn is_raw_text(namespace: Namespace, tag: &str) -> bool {
+	html_info(namespace, tag).is_some_and(|i| i.content == ContentModel::RawText)
+}
+
+/// The parser drops a newline right after the start tag of `pre`, `textarea` and `listing`,
+/// so content starting with a newline needs an extra one to keep it
+pub fn needs_leading_newline(namespace: Namespace, tag: &str, text: &str) -> bool {
+	namespace == Namespace::Html
+		&& matches!(tag, "pre" | "textarea" | "listing")
+		&& text.starts_with('\n')
+}
+
+// Whitespace around inline elements is significant
+fn is_inline(namespace: Namespace, tag: &str) -> bool {
+	match namespace {
+		Namespace::Html => element_info(tag).is_none_or(|i| i.display == Display::Inline),
+		Namespace::Svg => false,
+		Namespace::MathMl => true,
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOptions {
+	/// Prepend `<!DOCTYPE html>`, for full documents
+	pub doctype: bool,
+	/// Indent nested elements with the given number of spaces.
+	/// `None` produces minified output.
+	pub indent: Option<usize>,
+}
+
+impl HtmlOptions {
+	pub fn minified() -> Self {
+		Self::default()
+	}
+
+	pub fn pretty() -> Self {
+		HtmlOptions {
+			indent: Some(2),
+			..Default::default()
+		}
+	}
+
+	pub fn doctype(mut self, doctype: bool) -> Self {
+		self.doctype = doctype;
+		self
+	}
+}
+
+/// Escapes `&`, `<` and `>` in a text node
+pub fn escape_text(out: &mut impl Write, text: &str) -> fmt::Result {
+	escape(out, text, |c| match c {
+		'&' => Some("&amp;"),
+		'<' => Some("&lt;"),
+		'>' => Some("&gt;"),
+		'\u{a0}' => Some("&nbsp;"),
+		_ => None,
+	})
+}
+
+/// Escapes `&` and `"` in a double-quoted attribute value
+pub fn escape_attr(out: &mut impl Write, value: &str) -> fmt::Result {
+	escape(out, value, |c| match c {
+		'&' => Some("&amp;"),
+		'"' => Some("&quot;"),
+		'\u{a0}' => Some("&nbsp;"),
+		_ => None,
+	})
+}
+
+/// Raw text can't be escaped, but it must not close its element early.
+/// Only `</script` and `</style` are changed, in any case, other text is written as is.
+pub fn write_raw_text(out: &mut impl Write, text: &str) -> fmt::Result {
+	let mut last = 0;
+	for (index, _) in text.match_indices("</") {
+		let name = &text.as_bytes()[index + 2..];
+		let closes = ["script", "style"].iter().any(|tag| {
+			name.len() >= tag.len() && name[..tag.len()].eq_ignore_ascii_case(tag.as_bytes())
+		});
+
+		if closes {
+			out.write_str(&text[last..index])?;
+			out.write_str("<\\/")?;
+			last = index + 2;
+		}
+	}
+	out.write_str(&text[last..])
+}
+
+fn escape(
+	out: &mut impl Write,
+	text: &str,
+	entity: impl Fn(char) -> Option<&'static str>,
+) -> fmt::Result {
+	let mut last = 0;
+	for (index, c) in text.char_indices() {
+		if let Some(entity) = entity(c) {
+			out.write_str(&text[last..index])?;
+			out.write_str(entity)?;
+			last = index + c.len_utf8();
+		}
+	}
+	out.write_str(&text[last..])
+}
+
+/// Writes the start tag up to, but not including, the closing `>`.
+/// `class` and `style` tokens are merged with the attributes of the same name.
+pub fn write_start_tag<'a>(
+	out: &mut impl Write,
+	tag: &str,
+	attributes: impl Iterator<Item = (&'a str, &'a str)>,
+	class: impl Iterator<Item = &'a str>,
+	style: impl Iterator<Item = (&'a str, &'a str)>,
+) -> fmt::Result {
+	write!(out, "<{}", tag)?;
+
+	let mut class_attr = None;
+	let mut style_attr = None;
+	for (name, value) in attributes {
+		match name {
+			"class" => class_attr = Some(value),
+			"style" => style_attr = Some(value),
+			_ => write_attribute(out, name, value)?,
+		}
+	}
+
+	write_class_and_style(out, class_attr, class, style_attr, style)
+}
+
+pub fn write_attribute(out: &mut impl Write, name: &str, value: &str) -> fmt::Result {
+	write!(out, " {}=\"", name)?;
+	escape_attr(out, value)?;
+	out.write_char('"')
+}
+
+/// Writes `class` and `style` attributes, the values of the attributes
+/// go first, followed by the separate tokens and properties.
+pub fn write_class_and_style<'a>(
+	out: &mut impl Write,
+	class_attr: Option<&'a str>,
+	class: impl Iterator<Item = &'a str>,
+	style_attr: Option<&'a str>,
+	style: impl Iterator<Item = (&'a str, &'a str)>,
+) -> fmt::Result {
+	let mut tokens = class_attr
+		.into_iter()
+		.flat_map(|c| c.split_ascii_whitespace())
+		.collect::<Vec<_>>();
+
+	for token in class {
+		if !tokens.contains(&token) {
+			tokens.push(token);
+		}
+	}
+
+	if !tokens.is_empty() {
+		out.write_str(" class=\"")?;
+		escape_attr(out, &tokens.join(" "))?;
+		out.write_char('"')?;
+	}
+
+	let mut declarations = style_attr
+		.map(|s| s.trim().trim_end_matches(';').to_owned())
+		.filter(|s| !s.is_empty())
+		.into_iter()
+		.collect::<Vec<_>>();
+
+	for (name, value) in style {
+		declarations.push(format!("{}:{}", name, value));
+	}
+
+	if !declarations.is_empty() {
+		out.write_str(" style=\"")?;
+		escape_attr(out, &declarations.join(";"))?;
+		out.write_char('"')?;
+	}
+
+	Ok(())
+}
+
+impl<'a> StaticElement<'a> {
+	pub fn write_html(&self, out: &mut impl Write, options: &HtmlOptions) -> fmt::Result {
+		if options.doctype {
+			out.write_str(DOCTYPE)?;
+			if options.indent.is_some() {
+				out.write_char('\n')?;
+			}
+		}
+
+		self.write_element(out, options, 0)
+	}
+
+	fn write_element(
+		&self,
+		out: &mut impl Write,
+		options: &HtmlOptions,
+		depth: usize,
+	) -> fmt::Result {
+		write_start_tag(
+			out,
+			self.tag,
+			self.attr.borrow().iter().map(|(k, v)| (*k, *v)),
+			self.class.borrow().iter().copied(),
+			self.style.borrow().iter().map(|(k, v)| (*k, *v)),
+		)?;
+		out.write_char('>')?;
+
+		if is_void(self.namespace, self.tag) {
+			debug_assert!(
+				self.children.borrow().is_empty(),
+				"Void element <{}> can't have children",
+				self.tag
+			);
+			return Ok(());
+		}
+
+		let children = self.children.borrow();
+		if let Some(StaticNode::Text(text)) = children.first() {
+			if needs_leading_newline(self.namespace, self.tag, &text.text.borrow()) {
+				out.write_char('\n')?;
+			}
+		}
+
+		// Only indent block elements without text, otherwise whitespace would change the content
+		let indent = options.indent.filter(|_| {
+			let preformatted = self.tag == "pre"
+				|| html_info(self.namespace, self.tag)
+					.is_some_and(|i| i.content != ContentModel::Normal);
+
+			!preformatted
+				&& !children.is_empty()
+				&& children.iter().all(|c| match c {
+					StaticNode::Element(e) => !is_inline(e.namespace, e.tag),
+					StaticNode::Text(_) => false,
+				})
+		});
+
+		for child in children.iter() {
+			if let Some(indent) = indent {
+				write!(out, "\n{:width$}", "", width = (depth + 1) * indent)?;
+			}
+
+			match child {
+				StaticNode::Element(element) => element.write_element(out, options, depth + 1)?,
+				StaticNode::Text(text) if is_raw_text(self.namespace, self.tag) => {
+					write_raw_text(out, &text.text.borrow())?
+				}
+				StaticNode::Text(text) => escape_text(out, &text.text.borrow())?,
+			}
+		}
+
+		if let Some(indent) = indent {
+			write!(out, "\n{:width$}", "", width = depth * indent)?;
+		}
+
+		write!(out, "</{}>", self.tag)
+	}
+}
@@ -1,20 +1,43 @@
 use std::cell::RefCell;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::rc::Rc;
 
 pub use bumpalo;
+use indexmap::IndexMap;
 
+use super::css::{Stylesheet, STYLE_ATTRIBUTE};
+use super::event::ListenerOptions;
+use super::prop::{reflected_attribute, PropValue};
+use super::serialize::{write_raw_text, HtmlOptions};
 use super::HtmlBackend;
 use crate::tree::Tree;
-use crate::{Backend, Markup};
+use crate::{Backend, Markup, Namespace};
 
 #[derive(Clone, Debug)]
 pub struct StaticHtml<'a> {
 	pub bump: &'a bumpalo::Bump,
+	/// Stylesheets used during the render, in the order of the first use
+	styles: Rc<RefCell<IndexMap<u64, &'static Stylesheet>>>,
 }
 
 impl<'a> StaticHtml<'a> {
 	pub fn new(bump: &'a bumpalo::Bump) -> Self {
-		Self { bump }
+		Self {
+			bump,
+			styles: Default::default(),
+		}
+	}
+
+	/// `<style>` elements of the stylesheets used so far, to be inlined into the page head
+	pub fn styles(&self) -> String {
+		let mut buffer = String::new();
+		for sheet in self.styles.borrow().values() {
+			write!(buffer, "<style {}=\"{}\">", STYLE_ATTRIBUTE, sheet.class()).unwrap();
+			write_raw_text(&mut buffer, &sheet.to_css()).unwrap();
+			buffer.push_str("</style>");
+		}
+		buffer
 	}
 }
 
@@ -26,6 +49,7 @@ pub enum StaticNode<'a> {
 
 #[derive(Debug)]
 pub struct StaticElement<'a> {
+	pub namespace: Namespace,
 	pub tag: &'a str,
 	pub parent: RefCell<Option<&'a StaticElement<'a>>>,
 	pub children: RefCell<Vec<StaticNode<'a>>>,
@@ -36,7 +60,12 @@ pub struct StaticElement<'a> {
 
 impl<'a> StaticElement<'a> {
 	pub fn new(tag: &'a str) -> Self {
+		Self::new_ns(Namespace::Html, tag)
+	}
+
+	pub fn new_ns(namespace: Namespace, tag: &'a str) -> Self {
 		StaticElement {
+			namespace,
 			tag,
 			parent: RefCell::new(None),
 			children: RefCell::new(Vec::new()),
@@ -48,59 +77,9 @@ impl<'a> StaticElement<'a> {
 }
 
 impl<'a> StaticElement<'a> {
+	/// Minified HTML of the element, see [`StaticElement::write_html`]
 	pub fn to_html(&self, buffer: &mut String) -> Result<(), std::fmt::Error> {
-		use std::fmt::Write;
-		write!(buffer, "<{} ", self.tag)?;
-
-		let mut class: Option<&'a str> = None;
-		for (name, value) in self.attr.borrow().iter() {
-			if *name == "class" {
-				class = Some(value);
-			} else {
-				write!(buffer, r#"{}="{}""#, name, value)?;
-			}
-		}
-
-		{
-			let classes = self.class.borrow();
-			if class.is_some() || classes.len() > 0 {
-				write!(buffer, " class=\"")?;
-				for class in classes.iter() {
-					write!(buffer, "{} ", class)?;
-				}
-				if let Some(class) = class {
-					write!(buffer, "{}", class)?;
-				}
-				write!(buffer, "\"")?;
-			}
-		}
-
-		{
-			let styles = self.style.borrow();
-			if styles.len() > 0 {
-				write!(buffer, " style=\"")?;
-				for (name, value) in styles.iter() {
-					write!(buffer, "{}:{};", name, value)?;
-				}
-				write!(buffer, "\"")?;
-			}
-		}
-
-		write!(buffer, ">")?;
-
-		let children = self.children.borrow();
-		for child in children.iter() {
-			match child {
-				StaticNode::Element(node) => {
-					node.to_html(buffer)?;
-				}
-				StaticNode::Text(text) => {
-					write!(buffer, "{}", text.text.borrow())?;
-				}
-			}
-		}
-		write!(buffer, "</{}>", self.tag)?;
-		Ok(())
+		self.write_html(buffer, &HtmlOptions::minified())
 	}
 }
 
@@ -144,6 +123,8 @@ impl<'a> StaticNode<'a> {
 }
 
 impl<'a> HtmlBackend for StaticHtml<'a> {
+	type Listener = ();
+
 	fn set_attribute(&self, node: &Self::Element, name: &str, value: &str) {
 		let name = self.bump.alloc_str(name);
 		let value = self.bump.alloc_str(value);
@@ -172,6 +153,36 @@ impl<'a> HtmlBackend for StaticHtml<'a> {
 	fn remove_class(&self, node: &Self::Element, class: &str) {
 		node.class.borrow_mut().remove(class);
 	}
+
+	fn set_dom_property(&self, node: &Self::Element, name: &str, value: &PropValue) {
+		if let Some(name) = reflected_attribute(name) {
+			match value.to_attribute() {
+				Some(value) => self.set_attribute(node, &name, &value),
+				None => self.remove_attribute(node, &name),
+			}
+		}
+	}
+
+	fn get_dom_property(&self, _node: &Self::Element, _name: &str) -> Option<PropValue<'static>> {
+		None
+	}
+
+	fn add_listener(
+		&self,
+		_tree: &Tree<Self>,
+		_event: &'static str,
+		_options: ListenerOptions,
+		_handler: Rc<dyn Fn(Self::Event)>,
+	) -> Self::Listener {
+	}
+
+	fn remove_listener(&self, _tree: &Tree<Self>, _listener: Self::Listener) {}
+
+	fn dispatch_event(&self, _element: &Self::Element, _event: Self::Event) {}
+
+	fn mount_stylesheet(&self, sheet: &'static Stylesheet) {
+		self.styles.borrow_mut().entry(sheet.hash()).or_insert(sheet);
+	}
 }
 
 impl<'a> Backend for StaticHtml<'a> {
@@ -223,6 +234,10 @@ impl<'a> Backend for StaticHtml<'a> {
 		self.bump.alloc_with(|| StaticElement::new(tag))
 	}
 
+	fn create_element_ns(&self, namespace: Namespace, tag: &'static str) -> Self::Element {
+		self.bump.alloc_with(|| StaticElement::new_ns(namespace, tag))
+	}
+
 	fn create_text(&self, data: &str) -> Self::Text {
 		let data = self.bump.alloc_str(data);
 		self.bump.alloc_with(|| StaticText::new(data))
@@ -249,7 +264,10 @@ impl<'a> Backend for StaticHtml<'a> {
 	}
 
 	fn node_to_text(node: Self::Node) -> Option<Self::Text> {
-		todo!()
+		match node {
+			StaticNode::Text(text) => Some(text),
+			_ => None,
+		}
 	}
 
 	fn print_node(node: &Self::Node) {
@@ -272,7 +290,15 @@ fn find_index<'a>(parent: &'a StaticElement<'a>, node: StaticNode<'a>) -> usize
 		.0
 }
 
-pub fn render<'a, M: Markup<StaticHtml<'a>>>(backend: StaticHtml<'a>, mut markup: M) -> String {
+pub fn render<'a, M: Markup<StaticHtml<'a>>>(backend: StaticHtml<'a>, markup: M) -> String {
+	render_with(backend, markup, &HtmlOptions::minified())
+}
+
+pub fn render_with<'a, M: Markup<StaticHtml<'a>>>(
+	backend: StaticHtml<'a>,
+	mut markup: M,
+	options: &HtmlOptions,
+) -> String {
 	let tree = Tree::ephemeral_root(backend);
 
 	markup.render(&tree);
@@ -280,8 +306,203 @@ pub fn render<'a, M: Markup<StaticHtml<'a>>>(backend: StaticHtml<'a>, mut markup
 
 	StaticHtml::node_to_element(tree.node().clone())
 		.unwrap()
-		.to_html(&mut buffer)
+		.write_html(&mut buffer, options)
 		.unwrap();
 
 	buffer
 }
+
+/// Result of [`render_with_styles`]
+#[derive(Debug)]
+pub struct RenderedHtml {
+	pub html: String,
+	/// `<style>` elements for the page head, see [`StaticHtml::styles`]
+	pub styles: String,
+}
+
+/// Renders the markup and collects the stylesheets it uses
+pub fn render_with_styles<'a, M: Markup<StaticHtml<'a>>>(
+	backend: StaticHtml<'a>,
+	markup: M,
+) -> RenderedHtml {
+	let html = render(backend.clone(), markup);
+	RenderedHtml {
+		html,
+		styles: backend.styles(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::web::prelude::*;
+
+	#[test]
+	fn test_serialize() {
+		let bump = bumpalo::Bump::new();
+		let html = render(
+			StaticHtml::new(&bump),
+			div((
+				classlist("a"),
+				classname("b"),
+				attr("title", "\"quoted\" & more"),
+				property("color", "red"),
+				"<b>text</b>",
+				br(()),
+				input(attr("value", "x")),
+			)),
+		);
+
+		assert_eq!(
+			html,
+			r#"<div title="&quot;quoted&quot; &amp; more" class="a b" style="color:red">&lt;b&gt;text&lt;/b&gt;<br><input value="x"></div>"#
+		);
+	}
+
+	#[test]
+	fn test_serialize_raw_text() {
+		let bump = bumpalo::Bump::new();
+		let html = render(
+			StaticHtml::new(&bump),
+			script("if (a < b) document.write('</SCRIPT>' + '</div>')"),
+		);
+
+		assert_eq!(
+			html,
+			r#"<script>if (a < b) document.write('<\/SCRIPT>' + '</div>')</script>"#
+		);
+	}
+
+	#[test]
+	fn test_serialize_props() {
+		let bump = bumpalo::Bump::new();
+		let html = render(
+			StaticHtml::new(&bump),
+			input((
+				prop("value", "x"),
+				prop("readOnly", true),
+				prop("checked", false),
+				prop("scrollTop", 10),
+			)),
+		);
+
+		// Properties without an attribute are not rendered
+		assert_eq!(html, r#"<input readonly="" value="x">"#);
+	}
+
+	#[test]
+	fn test_serialize_svg() {
+		let bump = bumpalo::Bump::new();
+		let html = render(
+			StaticHtml::new(&bump),
+			div(svg::svg((
+				attr("viewBox", "0 0 10 10"),
+				svg::path(attr("d", "M0 0")),
+				svg::style("a</b"),
+			))),
+		);
+
+		assert_eq!(
+			html,
+			r#"<div><svg viewBox="0 0 10 10"><path d="M0 0"></path><style>a&lt;/b</style></svg></div>"#
+		);
+	}
+
+	#[test]
+	fn test_serialize_pretty() {
+		let bump = bumpalo::Bump::new();
+		let html = render_with(
+			StaticHtml::new(&bump),
+			html((head(()), body(div("text")))),
+			&HtmlOptions::pretty().doctype(true),
+		);
+
+		assert_eq!(
+			html,
+			"<!DOCTYPE html>\n<html>\n  <head></head>\n  <body>\n    <div>text</div>\n  </body>\n</html>"
+		);
+	}
+
+	#[test]
+	fn test_serialize_inline() {
+		let bump = bumpalo::Bump::new();
+		let html = render_with(
+			StaticHtml::new(&bump),
+			div((p("a"), hr(()), ul(li(em("b"))))),
+			&HtmlOptions::pretty(),
+		);
+
+		assert_eq!(
+			html,
+			"<div>\n  <p>a</p>\n  <hr>\n  <ul>\n    <li><em>b</em></li>\n  </ul>\n</div>"
+		);
+	}
+
+	#[test]
+	fn test_serialize_leading_newline() {
+		let bump = bumpalo::Bump::new();
+		let html = render(
+			StaticHtml::new(&bump),
+			div((pre("\na"), textarea("\nb"), pre("c\n"), svg::text("\nd"))),
+		);
+
+		assert_eq!(
+			html,
+			"<div><pre>\n\na</pre><textarea>\n\nb</textarea><pre>c\n</pre><text>\nd</text></div>"
+		);
+	}
+
+	#[test]
+	fn test_serialize_listeners() {
+		// Listeners are skipped, DOM-typed handlers are fine
+		let bump = bumpalo::Bump::new();
+		let html = render(
+			StaticHtml::new(&bump),
+			button((
+				on("click", |_: web_sys::Event| {}),
+				on_click(|_: web_sys::MouseEvent| {}),
+				"Click",
+			)),
+		);
+
+		assert_eq!(html, "<button>Click</button>");
+	}
+
+	#[test]
+	fn test_render_styles() {
+		let bump = bumpalo::Bump::new();
+		let sheet = css!("color: red");
+		let rendered = render_with_styles(StaticHtml::new(&bump), div((sheet, span((sheet, "a")))));
+
+		let class = sheet.class();
+		assert_eq!(
+			rendered.html,
+			format!(
+				r#"<div class="{c}"><span class="{c}">a</span></div>"#,
+				c = class
+			)
+		);
+		assert_eq!(
+			rendered.styles,
+			format!(
+				r#"<style data-skima-style="{c}">.{c}{{color: red;}}</style>"#,
+				c = class
+			)
+		);
+	}
+
+	#[test]
+	fn test_render_styles_escaped() {
+		let bump = bumpalo::Bump::new();
+		let sheet = css!(r#"&::after { content: "</style>" }"#);
+		let rendered = render_with_styles(StaticHtml::new(&bump), div(sheet));
+
+		assert_eq!(
+			rendered.styles,
+			format!(
+				r#"<style data-skima-style="{c}">.{c}::after{{ content: "<\/style>" }}</style>"#,
+				c = sheet.class()
+			)
+		);
+	}
+}
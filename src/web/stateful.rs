@@ -3,7 +3,7 @@ use std::fmt::Display;
 use std::marker::PhantomData;
 use std::rc::Rc;
 
-use super::context::{DefaultExt, DynInit, MaybeExtension, StatefulContext, WithEffects};
+use super::context::{DefaultExt, DynInit, MaybeExtension, StatefulContext, WithEffects, WithMemo};
 use crate::console_log;
 use crate::tree::Tree;
 use crate::web::context::HasContext;
@@ -54,6 +54,7 @@ where
 	B: Backend + 'a,
 	E: DynInit + 'a,
 	E: MaybeExtension<WithEffects<B, E>>,
+	E: MaybeExtension<WithMemo>,
 {
 	fn has_own_node() -> bool {
 		M::has_own_node()
@@ -123,8 +124,17 @@ where
 			with_arena.reset_effects_alive();
 		}
 
+		if let Some(with_memo @ WithMemo { .. }) = context.ext.try_get() {
+			with_memo.reset_keys();
+		}
+
 		if let Some(factory) = self.factory.take() {
 			let mut markup = factory(context);
+
+			if let Some(with_memo @ WithMemo { .. }) = context.ext.try_get() {
+				with_memo.drop_unused();
+			}
+
 			markup.diff(prev.rendered.as_mut().unwrap(), tree);
 			self.rendered = Some(markup);
 		} else if let Some(markup) = self.rendered.as_mut() {
@@ -0,0 +1,545 @@
+//! Streaming server-side rendering.
+//!
+//! Unlike [`StaticHtml`](super::ssr::StaticHtml), [`HtmlStream`] doesn't keep
+//! the rendered nodes, everything is written into the sink as soon as it's
+//! rendered. The start tag of an element is completed when its first child
+//! is created, attributes set after that are ignored with a warning.
+//! Attributes are written immediately, so they can't be removed and
+//! setting one twice writes it twice. Only `class` and `style` are collected
+//! until the start tag is complete.
+//!
+//! The stream is write-only, updates of the rendered markup are ignored.
+//! Output is always minified, prepend [`DOCTYPE`](super::serialize::DOCTYPE)
+//! yourself for full documents.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Debug, Write};
+use std::io;
+use std::rc::Rc;
+
+use super::event::ListenerOptions;
+use super::prop::{reflected_attribute, PropValue};
+use super::serialize::{
+	escape_text, is_raw_text, is_void, needs_leading_newline, write_attribute,
+	write_class_and_style, write_raw_text,
+};
+use super::HtmlBackend;
+use crate::tree::Tree;
+use crate::{render_subtree, Backend, Markup, Namespace};
+
+/// Writes into a sink that lives for `'a`
+#[derive(Clone)]
+pub struct HtmlStream<'a> {
+	state: Rc<StreamState<'a>>,
+}
+
+impl<'a> Debug for HtmlStream<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("HtmlStream").finish_non_exhaustive()
+	}
+}
+
+struct StreamState<'a> {
+	sink: Rc<RefCell<dyn Write + 'a>>,
+	failed: Cell<bool>,
+	next_id: Cell<usize>,
+	// Elements that are not closed yet, the innermost is the last.
+	// The first one is the fragment, it has no tags.
+	open: RefCell<Vec<OpenElement>>,
+}
+
+struct OpenElement {
+	id: usize,
+	namespace: Namespace,
+	tag: &'static str,
+	// The start tag is complete, the element has children
+	started: bool,
+	class_attr: Option<String>,
+	class: BTreeSet<String>,
+	style_attr: Option<String>,
+	style: BTreeMap<String, String>,
+}
+
+impl OpenElement {
+	fn new(id: usize, namespace: Namespace, tag: &'static str, started: bool) -> Self {
+		OpenElement {
+			id,
+			namespace,
+			tag,
+			started,
+			class_attr: None,
+			class: Default::default(),
+			style_attr: None,
+			style: Default::default(),
+		}
+	}
+
+	fn finish_start_tag(&mut self, out: &mut dyn Write) -> fmt::Result {
+		self.started = true;
+		write_class_and_style(
+			out,
+			self.class_attr.as_deref(),
+			self.class.iter().map(|c| c.as_str()),
+			self.style_attr.as_deref(),
+			self.style.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+		)?;
+		out.write_char('>')
+	}
+}
+
+impl<'a> StreamState<'a> {
+	fn write(&self, f: impl FnOnce(&mut dyn Write) -> fmt::Result) {
+		if self.failed.get() {
+			return;
+		}
+
+		if f(&mut *self.sink.borrow_mut()).is_err() {
+			self.failed.set(true);
+		}
+	}
+
+	fn next_id(&self) -> usize {
+		let id = self.next_id.get();
+		self.next_id.set(id + 1);
+		id
+	}
+
+	/// Completes the start tag of the innermost element before its first child
+	fn start_content(&self) {
+		if let Some(element) = self.open.borrow_mut().last_mut() {
+			if !element.started {
+				self.write(|out| element.finish_start_tag(out));
+			}
+		}
+	}
+
+	/// Runs `f` if the start tag of the element is not complete yet
+	fn with_start_tag<R>(
+		&self,
+		node: &StreamNode,
+		f: impl FnOnce(&mut OpenElement) -> R,
+	) -> Option<R> {
+		let mut open = self.open.borrow_mut();
+		match open.iter_mut().rev().find(|e| e.id == node.id) {
+			Some(element) if !element.started => Some(f(element)),
+			_ => {
+				tracing::warn!(
+					"HtmlStream: start tag of {:?} is already written, ignoring",
+					node
+				);
+				None
+			}
+		}
+	}
+
+	fn close(&self, node: &StreamNode) {
+		let mut element = self.open.borrow_mut().pop().unwrap();
+		assert_eq!(
+			element.id, node.id,
+			"HtmlStream: elements should be attached in the order they were created"
+		);
+
+		self.write(|out| {
+			if !element.started {
+				element.finish_start_tag(out)?;
+			}
+
+			if !is_void(element.namespace, element.tag) {
+				write!(out, "</{}>", element.tag)?;
+			}
+
+			Ok(())
+		});
+	}
+}
+
+#[derive(Clone)]
+pub struct StreamNode<'a> {
+	id: usize,
+	// `None` for text nodes
+	tag: Option<&'static str>,
+	state: Rc<StreamState<'a>>,
+}
+
+impl<'a> Debug for StreamNode<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.tag {
+			Some(tag) => write!(f, "<{}> #{}", tag, self.id),
+			None => write!(f, "#text #{}", self.id),
+		}
+	}
+}
+
+impl<'a> HtmlStream<'a> {
+	fn new(sink: Rc<RefCell<dyn Write + 'a>>) -> Self {
+		HtmlStream {
+			state: Rc::new(StreamState {
+				sink,
+				failed: Cell::new(false),
+				next_id: Cell::new(0),
+				open: RefCell::new(Vec::new()),
+			}),
+		}
+	}
+
+	// Root element that is never written itself
+	fn fragment(&self) -> StreamNode<'a> {
+		let id = self.state.next_id();
+		self.state
+			.open
+			.borrow_mut()
+			.push(OpenElement::new(id, Namespace::Html, "", true));
+
+		StreamNode {
+			id,
+			tag: Some(""),
+			state: self.state.clone(),
+		}
+	}
+
+	fn open_element(&self, namespace: Namespace, tag: &'static str) -> StreamNode<'a> {
+		self.state.start_content();
+		self.state.write(|out| write!(out, "<{}", tag));
+
+		let id = self.state.next_id();
+		self.state
+			.open
+			.borrow_mut()
+			.push(OpenElement::new(id, namespace, tag, false));
+
+		StreamNode {
+			id,
+			tag: Some(tag),
+			state: self.state.clone(),
+		}
+	}
+}
+
+impl<'a> Backend for HtmlStream<'a> {
+	type Element = StreamNode<'a>;
+	type Text = StreamNode<'a>;
+	type Node = StreamNode<'a>;
+	type Cursor = ();
+	type Event = ();
+
+	fn replace(node: &Self::Node, _prev: &Self::Node) {
+		tracing::warn!("HtmlStream is write-only, ignoring replace of {:?}", node);
+	}
+
+	fn insert(_cursor: Self::Cursor, node: &Self::Node) {
+		// Nodes are always written in place, only elements have to be closed
+		if node.tag.is_some() {
+			node.state.close(node)
+		}
+	}
+
+	fn remove(node: &Self::Node) {
+		tracing::warn!("HtmlStream is write-only, ignoring remove of {:?}", node);
+	}
+
+	fn cursor_beginning_of(_node: &Self::Element) -> Self::Cursor {}
+
+	fn cursor_after(_node: &Self::Node) -> Self::Cursor {}
+
+	fn create_element(&self, tag: &'static str) -> Self::Element {
+		self.create_element_ns(Namespace::Html, tag)
+	}
+
+	fn create_element_ns(&self, namespace: Namespace, tag: &'static str) -> Self::Element {
+		self.open_element(namespace, tag)
+	}
+
+	fn create_text(&self, data: &str) -> Self::Text {
+		let (raw, leading_newline) = match self.state.open.borrow().last() {
+			Some(e) => (
+				is_raw_text(e.namespace, e.tag),
+				!e.started && needs_leading_newline(e.namespace, e.tag, data),
+			),
+			None => (false, false),
+		};
+
+		self.state.start_content();
+		self.state.write(|mut out| {
+			if leading_newline {
+				out.write_char('\n')?;
+			}
+
+			if raw {
+				write_raw_text(&mut out, data)
+			} else {
+				escape_text(&mut out, data)
+			}
+		});
+
+		StreamNode {
+			id: self.state.next_id(),
+			tag: None,
+			state: self.state.clone(),
+		}
+	}
+
+	fn set_text(&self, text: &Self::Text, _data: &str) {
+		tracing::warn!("HtmlStream is write-only, ignoring update of {:?}", text);
+	}
+
+	fn text_to_node(text: Self::Text) -> Self::Node {
+		text
+	}
+
+	fn element_to_node(element: Self::Element) -> Self::Node {
+		element
+	}
+
+	fn node_to_element(node: Self::Node) -> Option<Self::Element> {
+		node.tag.is_some().then_some(node)
+	}
+
+	fn node_to_text(node: Self::Node) -> Option<Self::Text> {
+		node.tag.is_none().then_some(node)
+	}
+
+	fn print_node(node: &Self::Node) {
+		println!("{:?}", node)
+	}
+}
+
+impl<'a> HtmlBackend for HtmlStream<'a> {
+	type Listener = ();
+
+	fn set_attribute(&self, element: &Self::Element, name: &str, value: &str) {
+		self.state.with_start_tag(element, |e| match name {
+			"class" => e.class_attr = Some(value.to_owned()),
+			"style" => e.style_attr = Some(value.to_owned()),
+			_ => self.state.write(|out| write_attribute(out, name, value)),
+		});
+	}
+
+	fn remove_attribute(&self, element: &Self::Element, name: &str) {
+		// Other attributes are already written, or were never set
+		self.state.with_start_tag(element, |e| match name {
+			"class" => e.class_attr = None,
+			"style" => e.style_attr = None,
+			_ => {}
+		});
+	}
+
+	fn set_property(&self, element: &Self::Element, name: &str, value: &str) {
+		self.state.with_start_tag(element, |e| {
+			e.style.insert(name.to_owned(), value.to_owned())
+		});
+	}
+
+	fn remove_property(&self, element: &Self::Element, name: &str) {
+		self.state.with_start_tag(element, |e| e.style.remove(name));
+	}
+
+	fn add_class(&self, element: &Self::Element, class: &str) {
+		self.state
+			.with_start_tag(element, |e| e.class.insert(class.to_owned()));
+	}
+
+	fn remove_class(&self, element: &Self::Element, class: &str) {
+		self.state
+			.with_start_tag(element, |e| e.class.remove(class));
+	}
+
+	fn set_dom_property(&self, element: &Self::Element, name: &str, value: &PropValue) {
+		if let Some(name) = reflected_attribute(name) {
+			match value.to_attribute() {
+				Some(value) => self.set_attribute(element, &name, &value),
+				None => self.remove_attribute(element, &name),
+			}
+		}
+	}
+
+	fn get_dom_property(&self, _element: &Self::Element, _name: &str) -> Option<PropValue<'static>> {
+		None
+	}
+
+	fn add_listener(
+		&self,
+		_tree: &Tree<Self>,
+		_event: &'static str,
+		_options: ListenerOptions,
+		_handler: Rc<dyn Fn(Self::Event)>,
+	) -> Self::Listener {
+	}
+
+	fn remove_listener(&self, _tree: &Tree<Self>, _listener: Self::Listener) {}
+
+	fn dispatch_event(&self, _element: &Self::Element, _event: Self::Event) {}
+}
+
+// Lets the caller get the sink back after rendering
+struct Sink<W>(Option<W>);
+
+impl<W: Write> Write for Sink<W> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.0.as_mut().ok_or(fmt::Error)?.write_str(s)
+	}
+}
+
+fn stream<'a, W: Write + 'a, M: Markup<HtmlStream<'a>>>(out: W, mut markup: M) -> (W, fmt::Result) {
+	let sink = Rc::new(RefCell::new(Sink(Some(out))));
+	let stream = HtmlStream::new(sink.clone());
+
+	let tree = Tree::root(stream.fragment(), stream.clone());
+	render_subtree(&mut markup, &tree);
+	tree.clear();
+
+	let result = if stream.state.failed.get() {
+		Err(fmt::Error)
+	} else {
+		Ok(())
+	};
+
+	let out = sink.borrow_mut().0.take().unwrap();
+	(out, result)
+}
+
+/// Renders the markup into `out` and returns it back.
+/// `out` may be borrowed, e.g. `&mut String` or a [`fmt::Formatter`].
+pub fn render_to_fmt<'a, W: Write + 'a, M: Markup<HtmlStream<'a>>>(
+	out: W,
+	markup: M,
+) -> Result<W, fmt::Error> {
+	let (out, result) = stream(out, markup);
+	result.map(|_| out)
+}
+
+/// Renders the markup into `out` and returns it back.
+/// Writes are not buffered, wrap the sink into [`io::BufWriter`] if needed.
+pub fn render_to_io<'a, W: io::Write + 'a, M: Markup<HtmlStream<'a>>>(
+	out: W,
+	markup: M,
+) -> io::Result<W> {
+	let (out, result) = stream(
+		IoWriter {
+			inner: out,
+			error: None,
+		},
+		markup,
+	);
+
+	match (result, out.error) {
+		(Ok(()), _) => Ok(out.inner),
+		(Err(_), Some(error)) => Err(error),
+		(Err(_), None) => Err(io::Error::other("formatter error")),
+	}
+}
+
+pub fn render_to_string<'a, M: Markup<HtmlStream<'a>>>(markup: M) -> String {
+	stream(String::new(), markup).0
+}
+
+struct IoWriter<W> {
+	inner: W,
+	error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoWriter<W> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.inner.write_all(s.as_bytes()).map_err(|e| {
+			self.error = Some(e);
+			fmt::Error
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tap;
+	use crate::web::prelude::*;
+	use crate::web::ssr::{self, StaticHtml};
+
+	fn page<B: HtmlBackend>() -> impl Markup<B> {
+		div((
+			classlist("page"),
+			attr("id", "main"),
+			span("a < b"),
+			br(()),
+			script("if (a) { document.write('</b>') }"),
+		))
+	}
+
+	#[test]
+	fn test_stream() {
+		let bump = bumpalo::Bump::new();
+		assert_eq!(
+			render_to_string(page()),
+			ssr::render(StaticHtml::new(&bump), page())
+		);
+	}
+
+	#[test]
+	fn test_stream_leading_newline() {
+		let bump = bumpalo::Bump::new();
+		let markup = || div((pre("\na"), textarea((attr("rows", "2"), "\nb")), pre("c\n")));
+		assert_eq!(
+			render_to_string(markup()),
+			ssr::render(StaticHtml::new(&bump), markup())
+		);
+	}
+
+	#[test]
+	fn test_stream_attribute_after_child() {
+		let markup = div((
+			attr("id", "x"),
+			span("a"),
+			attr("title", "y"),
+			classlist("b"),
+			"c",
+		));
+		assert_eq!(
+			render_to_string(markup),
+			"<div id=\"x\"><span>a</span>c</div>"
+		);
+	}
+
+	struct Shared(Rc<RefCell<String>>);
+
+	impl Write for Shared {
+		fn write_str(&mut self, s: &str) -> fmt::Result {
+			self.0.borrow_mut().push_str(s);
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn test_stream_before_close() {
+		let out = Rc::new(RefCell::new(String::new()));
+		let seen = Rc::new(RefCell::new(String::new()));
+
+		let markup = div((
+			classlist("page"),
+			span("a"),
+			tap({
+				let out = out.clone();
+				let seen = seen.clone();
+				move |_: &Tree<_>| {
+					seen.replace(out.borrow().clone());
+				}
+			}),
+			"b",
+		));
+
+		render_to_fmt(Shared(out.clone()), markup).unwrap();
+		assert_eq!(&*seen.borrow(), "<div class=\"page\"><span>a</span>");
+		assert_eq!(&*out.borrow(), "<div class=\"page\"><span>a</span>b</div>");
+	}
+
+	#[test]
+	fn test_stream_borrowed() {
+		let mut out = String::from("<!DOCTYPE html>");
+		render_to_fmt(&mut out, span("a")).unwrap();
+		assert_eq!(out, "<!DOCTYPE html><span>a</span>");
+	}
+
+	#[test]
+	fn test_stream_io() {
+		let out = render_to_io(Vec::new(), (span("a"), "b")).unwrap();
+		assert_eq!(String::from_utf8(out).unwrap(), "<span>a</span>b");
+	}
+}
@@ -1,3 +1,4 @@
+use super::attr::claim_attribute;
 use super::{HtmlBackend, StringLike};
 use crate::tree::Tree;
 use crate::Markup;
@@ -17,6 +18,7 @@ impl<B: HtmlBackend, S1: StringLike, S2: StringLike> Markup<B> for Property<S1,
 	}
 
 	fn render(&mut self, tree: &Tree<B>) {
+		claim_attribute(tree, "style");
 		let element = B::node_to_element(tree.closest_node()).unwrap();
 		tree.backend
 			.set_property(&element, self.0.as_ref(), self.1.as_ref());
@@ -1,13 +1,15 @@
 use std::marker::PhantomData;
 
+use super::attr::{remove_unclaimed_attributes, start_claim};
 use super::HtmlBackend;
 use crate::tree::Tree;
 use crate::web::{Backend, Markup};
-use crate::{render_subtree, subtree};
+use crate::{render_subtree, subtree, Namespace};
 
 #[derive(Clone)]
 pub struct Tag<M: Markup<B>, B: Backend, const N: usize> {
 	tag: &'static str,
+	namespace: Namespace,
 	markup: M,
 	_b: PhantomData<B>,
 }
@@ -28,16 +30,29 @@ where
 		#[cfg(debug_assertions)]
 		tree.name.replace(std::borrow::Cow::Borrowed(&self.tag));
 
-		let node = B::element_to_node(tree.backend.create_element(self.tag));
+		let node = B::element_to_node(tree.create_element_ns(self.namespace, self.tag));
 		let prev = tree.set_node(node);
+
+		// Still hydrating if the element was claimed
+		let hydration = tree.hydration();
+		if hydration.is_some() {
+			start_claim(tree);
+		}
+
 		render_subtree(&mut self.markup, tree);
+
+		if let Some(hydration) = hydration {
+			remove_unclaimed_attributes(tree, &hydration);
+		}
+
+		tree.remove_unclaimed();
 		tree.attach(prev);
 	}
 
 	fn diff(&mut self, prev: &mut Self, tree: &Tree<B>) {
-		if prev.tag != self.tag {
+		if prev.tag != self.tag || prev.namespace != self.namespace {
 			// re-render
-			let element = B::element_to_node(tree.backend.create_element(self.tag));
+			let element = B::element_to_node(tree.create_element_ns(self.namespace, self.tag));
 
 			let prev = tree.set_node(element.into());
 			tree.clear();
@@ -65,43 +80,283 @@ where
 	}
 }
 
-pub macro define_tag($name:ident, $ty:literal) {
-	pub fn $name<M: Markup<B>, B: Backend>(markup: M) -> Tag<M, B, 1>
-	where
-		Tag<M, B, 1>: Markup<B>,
-	{
-		Tag {
-			tag: stringify!($name),
-			markup,
-			_b: PhantomData,
+pub macro define_tag {
+	($name:ident) => {
+		define_tag!($name, stringify!($name), Html);
+	},
+	($name:ident, $tag:expr, $ns:ident) => {
+		pub fn $name<M: Markup<B>, B: Backend>(markup: M) -> Tag<M, B, 1>
+		where
+			Tag<M, B, 1>: Markup<B>,
+		{
+			Tag {
+				tag: $tag,
+				namespace: Namespace::$ns,
+				markup,
+				_b: PhantomData,
+			}
 		}
 	}
 }
 
-pub mod html {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentModel {
+	Normal,
+	/// No children and no end tag
+	Void,
+	/// Text that is not escaped, e.g. `script`
+	RawText,
+	/// Text without child elements, e.g. `textarea`
+	EscapableRawText,
+}
+
+/// Default rendering of the element, used for formatting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Display {
+	Inline,
+	Block,
+	/// Not rendered, e.g. `head` or `script`
+	None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElementInfo {
+	pub tag: &'static str,
+	pub content: ContentModel,
+	pub display: Display,
+}
+
+macro_rules! html_elements {
+	($($name:ident: $content:ident, $display:ident;)*) => {
+		/// Elements of the WHATWG HTML standard
+		pub const HTML_ELEMENTS: &[ElementInfo] = &[$(
+			ElementInfo {
+				tag: stringify!($name),
+				content: ContentModel::$content,
+				display: Display::$display,
+			},
+		)*];
+
+
+		pub mod html {
+			use super::define_tag;
+
+			$(define_tag!($name);)*
+		}
+	};
+}
+
+html_elements! {
+	a: Normal, Inline;
+	abbr: Normal, Inline;
+	address: Normal, Block;
+	area: Void, Inline;
+	article: Normal, Block;
+	aside: Normal, Block;
+	audio: Normal, Inline;
+	b: Normal, Inline;
+	base: Void, None;
+	bdi: Normal, Inline;
+	bdo: Normal, Inline;
+	blockquote: Normal, Block;
+	body: Normal, Block;
+	br: Void, Inline;
+	button: Normal, Inline;
+	canvas: Normal, Inline;
+	caption: Normal, Block;
+	cite: Normal, Inline;
+	code: Normal, Inline;
+	col: Void, Block;
+	colgroup: Normal, Block;
+	data: Normal, Inline;
+	datalist: Normal, None;
+	dd: Normal, Block;
+	del: Normal, Inline;
+	details: Normal, Block;
+	dfn: Normal, Inline;
+	dialog: Normal, Block;
+	div: Normal, Block;
+	dl: Normal, Block;
+	dt: Normal, Block;
+	em: Normal, Inline;
+	embed: Void, Inline;
+	fieldset: Normal, Block;
+	figcaption: Normal, Block;
+	figure: Normal, Block;
+	footer: Normal, Block;
+	form: Normal, Block;
+	h1: Normal, Block;
+	h2: Normal, Block;
+	h3: Normal, Block;
+	h4: Normal, Block;
+	h5: Normal, Block;
+	h6: Normal, Block;
+	head: Normal, None;
+	header: Normal, Block;
+	hgroup: Normal, Block;
+	hr: Void, Block;
+	html: Normal, Block;
+	i: Normal, Inline;
+	iframe: Normal, Inline;
+	img: Void, Inline;
+	input: Void, Inline;
+	ins: Normal, Inline;
+	kbd: Normal, Inline;
+	label: Normal, Inline;
+	legend: Normal, Block;
+	li: Normal, Block;
+	link: Void, None;
+	main: Normal, Block;
+	map: Normal, Inline;
+	mark: Normal, Inline;
+	menu: Normal, Block;
+	meta: Void, None;
+	meter: Normal, Inline;
+	nav: Normal, Block;
+	noscript: Normal, Inline;
+	object: Normal, Inline;
+	ol: Normal, Block;
+	optgroup: Normal, Block;
+	option: Normal, Block;
+	output: Normal, Inline;
+	p: Normal, Block;
+	picture: Normal, Inline;
+	pre: Normal, Block;
+	progress: Normal, Inline;
+	q: Normal, Inline;
+	rp: Normal, Inline;
+	rt: Normal, Inline;
+	ruby: Normal, Inline;
+	s: Normal, Inline;
+	samp: Normal, Inline;
+	script: RawText, None;
+	search: Normal, Block;
+	section: Normal, Block;
+	select: Normal, Inline;
+	slot: Normal, Inline;
+	small: Normal, Inline;
+	source: Void, None;
+	span: Normal, Inline;
+	strong: Normal, Inline;
+	style: RawText, None;
+	sub: Normal, Inline;
+	summary: Normal, Block;
+	sup: Normal, Inline;
+	table: Normal, Block;
+	tbody: Normal, Block;
+	td: Normal, Block;
+	template: Normal, None;
+	textarea: EscapableRawText, Inline;
+	tfoot: Normal, Block;
+	th: Normal, Block;
+	thead: Normal, Block;
+	time: Normal, Inline;
+	title: EscapableRawText, None;
+	tr: Normal, Block;
+	track: Void, None;
+	u: Normal, Inline;
+	ul: Normal, Block;
+	var: Normal, Inline;
+	video: Normal, Inline;
+	wbr: Void, Inline;
+}
+
+/// Looks the element up in [`HTML_ELEMENTS`]
+pub fn element_info(tag: &str) -> Option<&'static ElementInfo> {
+	HTML_ELEMENTS.iter().find(|info| info.tag == tag)
+}
+
+/// Fails to compile when a void element is given children, used by the `html!` macro
+pub const fn check_children(tag: &str) {
+	let mut i = 0;
+	while i < HTML_ELEMENTS.len() {
+		let info = &HTML_ELEMENTS[i];
+		if str_eq(info.tag, tag) && matches!(info.content, ContentModel::Void) {
+			panic!("Void elements can't have children");
+		}
+		i += 1;
+	}
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+	let (a, b) = (a.as_bytes(), b.as_bytes());
+	if a.len() != b.len() {
+		return false;
+	}
+
+	let mut i = 0;
+	while i < a.len() {
+		if a[i] != b[i] {
+			return false;
+		}
+		i += 1;
+	}
+
+	true
+}
+
+pub mod svg {
+	use super::define_tag;
+
+	define_tag!(svg, "svg", Svg);
+	define_tag!(g, "g", Svg);
+	define_tag!(defs, "defs", Svg);
+	define_tag!(symbol, "symbol", Svg);
+	define_tag!(r#use, "use", Svg);
+	define_tag!(path, "path", Svg);
+	define_tag!(circle, "circle", Svg);
+	define_tag!(ellipse, "ellipse", Svg);
+	define_tag!(rect, "rect", Svg);
+	define_tag!(line, "line", Svg);
+	define_tag!(polyline, "polyline", Svg);
+	define_tag!(polygon, "polygon", Svg);
+	define_tag!(text, "text", Svg);
+	define_tag!(tspan, "tspan", Svg);
+	define_tag!(text_path, "textPath", Svg);
+	define_tag!(image, "image", Svg);
+	define_tag!(a, "a", Svg);
+	define_tag!(title, "title", Svg);
+	define_tag!(desc, "desc", Svg);
+	define_tag!(clip_path, "clipPath", Svg);
+	define_tag!(mask, "mask", Svg);
+	define_tag!(pattern, "pattern", Svg);
+	define_tag!(marker, "marker", Svg);
+	define_tag!(linear_gradient, "linearGradient", Svg);
+	define_tag!(radial_gradient, "radialGradient", Svg);
+	define_tag!(stop, "stop", Svg);
+	define_tag!(filter, "filter", Svg);
+	define_tag!(fe_gaussian_blur, "feGaussianBlur", Svg);
+	define_tag!(fe_offset, "feOffset", Svg);
+	define_tag!(fe_blend, "feBlend", Svg);
+	define_tag!(fe_color_matrix, "feColorMatrix", Svg);
+	define_tag!(foreign_object, "foreignObject", Svg);
+	define_tag!(style, "style", Svg);
+}
+
+pub mod mathml {
 	use super::define_tag;
 
-	define_tag!(div, 1);
-	define_tag!(span, 2);
-	define_tag!(a, 3);
-	define_tag!(input, 4);
-	define_tag!(br, 5);
-	define_tag!(img, 6);
-	define_tag!(button, 7);
-	define_tag!(label, 8);
-	define_tag!(textarea, 9);
-	define_tag!(h1, 10);
-	define_tag!(h2, 11);
-	define_tag!(h3, 12);
-	define_tag!(header, 13);
-	define_tag!(section, 14);
-	define_tag!(ul, 15);
-	define_tag!(li, 16);
-	define_tag!(video, 17);
-	define_tag!(source, 18);
-	define_tag!(html, 19);
-	define_tag!(head, 20);
-	define_tag!(script, 20);
-	define_tag!(link, 21);
-	define_tag!(body, 22);
+	define_tag!(math, "math", MathMl);
+	define_tag!(mi, "mi", MathMl);
+	define_tag!(mn, "mn", MathMl);
+	define_tag!(mo, "mo", MathMl);
+	define_tag!(ms, "ms", MathMl);
+	define_tag!(mtext, "mtext", MathMl);
+	define_tag!(mspace, "mspace", MathMl);
+	define_tag!(mrow, "mrow", MathMl);
+	define_tag!(mfrac, "mfrac", MathMl);
+	define_tag!(msqrt, "msqrt", MathMl);
+	define_tag!(mroot, "mroot", MathMl);
+	define_tag!(mstyle, "mstyle", MathMl);
+	define_tag!(msub, "msub", MathMl);
+	define_tag!(msup, "msup", MathMl);
+	define_tag!(msubsup, "msubsup", MathMl);
+	define_tag!(munder, "munder", MathMl);
+	define_tag!(mover, "mover", MathMl);
+	define_tag!(munderover, "munderover", MathMl);
+	define_tag!(mtable, "mtable", MathMl);
+	define_tag!(mtr, "mtr", MathMl);
+	define_tag!(mtd, "mtd", MathMl);
+	define_tag!(semantics, "semantics", MathMl);
+	define_tag!(annotation, "annotation", MathMl);
 }
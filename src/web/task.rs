@@ -0,0 +1,306 @@
+//! Async tasks bound to the lifetime of a component.
+//!
+//! [`StatefulContext::spawn`] runs a future on the [`Executor`] of the thread and
+//! cancels it when the component is dropped. [`StatefulContext::spawn_eq`] also
+//! cancels it and starts a new one when the dependencies change:
+//!
+//! ```ignore
+//! reactive(|cx| {
+//!     let id = cx.get::<Var<u32>>().get(cx);
+//!     cx.spawn_eq(id, move |this| async move {
+//!         let user = fetch_user(id).await;
+//!         this.with(|cx| cx.set(Some(user)));
+//!     });
+//!     ...
+//! })
+//! ```
+//!
+//! Cancelled futures are dropped without being polled again.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::task::{Context, Poll};
+
+use super::context::{Extension, HasContext, StatefulContext, WithCycle, WithEffects};
+use crate::Backend;
+
+pub type LocalFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Runs the futures of the spawned tasks.
+/// Tasks are spawned while the component is borrowed, so futures shouldn't be polled
+/// before [`Executor::spawn`] returns.
+pub trait Executor {
+	fn spawn(&self, future: LocalFuture);
+}
+
+/// Spawns with `wasm_bindgen_futures::spawn_local`, the default executor
+pub struct WasmExecutor;
+
+impl Executor for WasmExecutor {
+	fn spawn(&self, future: LocalFuture) {
+		wasm_bindgen_futures::spawn_local(future)
+	}
+}
+
+thread_local! {
+	static EXECUTOR: RefCell<Rc<dyn Executor>> = RefCell::new(Rc::new(WasmExecutor));
+}
+
+/// Replaces the executor of the current thread, e.g. to run tasks natively
+pub fn set_executor(executor: impl Executor + 'static) {
+	EXECUTOR.with(|e| e.replace(Rc::new(executor)));
+}
+
+/// Spawned future, cancelled when dropped
+pub struct Task {
+	_future: Rc<RefCell<Option<LocalFuture>>>,
+}
+
+/// Spawns the future on the executor of the thread
+pub fn spawn(future: impl Future<Output = ()> + 'static) -> Task {
+	let slot: Rc<RefCell<Option<LocalFuture>>> = Rc::new(RefCell::new(Some(Box::pin(future))));
+	let executor = EXECUTOR.with(|e| e.borrow().clone());
+	executor.spawn(Box::pin(Cancellable(Rc::downgrade(&slot))));
+	Task { _future: slot }
+}
+
+/// Polls the future while its [`Task`] is alive
+struct Cancellable(Weak<RefCell<Option<LocalFuture>>>);
+
+impl Future for Cancellable {
+	type Output = ();
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+		let Some(slot) = self.0.upgrade() else {
+			return Poll::Ready(());
+		};
+
+		// The task may be dropped while the future is polled, the `slot` keeps it until we return
+		let mut future = slot.borrow_mut();
+		let Some(inner) = future.as_mut() else {
+			return Poll::Ready(());
+		};
+
+		match inner.as_mut().poll(cx) {
+			Poll::Ready(()) => {
+				*future = None;
+				Poll::Ready(())
+			}
+			Poll::Pending => Poll::Pending,
+		}
+	}
+}
+
+/// Access to the context from a spawned task
+pub struct WeakContext<B: Backend, E> {
+	this: Weak<dyn HasContext<B, E>>,
+}
+
+impl<B: Backend, E> Clone for WeakContext<B, E> {
+	fn clone(&self) -> Self {
+		WeakContext {
+			this: self.this.clone(),
+		}
+	}
+}
+
+impl<B: Backend, E> WeakContext<B, E> {
+	/// `None` if the component is already gone
+	pub fn with<R>(&self, func: impl FnOnce(&mut StatefulContext<B, E>) -> R) -> Option<R> {
+		let this = self.this.upgrade()?;
+		let mut context = this.context();
+		Some(func(&mut context))
+	}
+}
+
+impl<B: Backend + 'static, E: 'static> StatefulContext<B, E>
+where
+	E: Extension<WithEffects<B, E>>,
+	E: Extension<WithCycle<B, E>>,
+{
+	/// Runs the future after the render, it's cancelled when the component is dropped
+	#[track_caller]
+	pub fn spawn<F, Fut>(&self, func: F)
+	where
+		F: FnOnce(WeakContext<B, E>) -> Fut + 'static,
+		Fut: Future<Output = ()> + 'static,
+	{
+		self.spawn_eq((), func)
+	}
+
+	/// Same as [`StatefulContext::spawn`], but the task is cancelled and spawned again
+	/// when `deps` change
+	#[track_caller]
+	pub fn spawn_eq<F, Fut, D>(&self, deps: D, func: F)
+	where
+		F: FnOnce(WeakContext<B, E>) -> Fut + 'static,
+		Fut: Future<Output = ()> + 'static,
+		D: PartialEq + 'static,
+	{
+		let cycle: &WithCycle<B, E> = self.ext.get();
+		let this = cycle.this.clone();
+		self.effect_eq_clean(deps, move |_| {
+			let task = spawn(func(WeakContext { this }));
+			move |_| drop(task)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+	use std::task::Waker;
+
+	use super::*;
+	use crate::tree::Tree;
+	use crate::web::mock::MockDom;
+
+	/// Polls the tasks when asked to
+	#[derive(Clone, Default)]
+	struct ManualExecutor(Rc<RefCell<Vec<LocalFuture>>>);
+
+	impl Executor for ManualExecutor {
+		fn spawn(&self, future: LocalFuture) {
+			self.0.borrow_mut().push(future)
+		}
+	}
+
+	impl ManualExecutor {
+		fn run(&self) {
+			let mut cx = Context::from_waker(Waker::noop());
+			let tasks = std::mem::take(&mut *self.0.borrow_mut());
+			for mut task in tasks {
+				if task.as_mut().poll(&mut cx).is_pending() {
+					self.0.borrow_mut().push(task)
+				}
+			}
+		}
+	}
+
+	/// Counts the drops of the futures
+	struct DropCounter(Rc<Cell<u32>>);
+
+	impl Drop for DropCounter {
+		fn drop(&mut self) {
+			self.0.set(self.0.get() + 1)
+		}
+	}
+
+	/// Runs the effects synchronously, unlike the reactive components
+	struct TestExt {
+		effects: WithEffects<MockDom, Self>,
+		cycle: WithCycle<MockDom, Self>,
+	}
+
+	impl Extension<WithEffects<MockDom, Self>> for TestExt {
+		fn get(&self) -> &WithEffects<MockDom, Self> {
+			&self.effects
+		}
+		fn get_mut(&mut self) -> &mut WithEffects<MockDom, Self> {
+			&mut self.effects
+		}
+	}
+
+	impl Extension<WithCycle<MockDom, Self>> for TestExt {
+		fn get(&self) -> &WithCycle<MockDom, Self> {
+			&self.cycle
+		}
+		fn get_mut(&mut self) -> &mut WithCycle<MockDom, Self> {
+			&mut self.cycle
+		}
+	}
+
+	type Component = RefCell<StatefulContext<MockDom, TestExt>>;
+
+	fn component() -> Rc<Component> {
+		Rc::new_cyclic(|this: &Weak<Component>| {
+			RefCell::new(StatefulContext {
+				tree: Tree::ephemeral_root(MockDom::new()),
+				ext: TestExt {
+					effects: Default::default(),
+					cycle: WithCycle { this: this.clone() },
+				},
+			})
+		})
+	}
+
+	fn render(component: &Component, id: u32, started: &Rc<Cell<u32>>, cancelled: &Rc<Cell<u32>>) {
+		let cx = component.borrow();
+		cx.ext.effects.reset_effects_alive();
+
+		let (started, cancelled) = (started.clone(), cancelled.clone());
+		cx.spawn_eq(id, move |this| async move {
+			let _counter = DropCounter(cancelled);
+			assert!(this.with(|_cx| ()).is_some());
+			started.set(started.get() + 1);
+			std::future::pending::<()>().await
+		});
+
+		cx.ext.effects.run_effects(&cx);
+	}
+
+	#[test]
+	fn test_spawn_cancel_on_drop() {
+		let executor = ManualExecutor::default();
+		set_executor(executor.clone());
+
+		let (started, cancelled) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+		let task = spawn({
+			let (started, cancelled) = (started.clone(), cancelled.clone());
+			async move {
+				let _counter = DropCounter(cancelled);
+				started.set(started.get() + 1);
+				std::future::pending::<()>().await
+			}
+		});
+
+		// Not polled before the executor runs
+		assert_eq!(started.get(), 0);
+		executor.run();
+		assert_eq!((started.get(), cancelled.get()), (1, 0));
+
+		// The future is dropped with the task, the executor drops the rest
+		drop(task);
+		assert_eq!(cancelled.get(), 1);
+		executor.run();
+		assert!(executor.0.borrow().is_empty());
+
+		set_executor(WasmExecutor);
+	}
+
+	#[test]
+	fn test_spawn_eq_cancel_on_deps() {
+		let executor = ManualExecutor::default();
+		set_executor(executor.clone());
+
+		let (started, cancelled) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+		let component = component();
+
+		render(&component, 1, &started, &cancelled);
+		executor.run();
+		assert_eq!((started.get(), cancelled.get()), (1, 0));
+
+		// Same deps, the task keeps running
+		render(&component, 1, &started, &cancelled);
+		executor.run();
+		assert_eq!((started.get(), cancelled.get()), (1, 0));
+
+		render(&component, 2, &started, &cancelled);
+		executor.run();
+		assert_eq!((started.get(), cancelled.get()), (2, 1));
+
+		// Dropping the component cleans up the effects
+		{
+			let cx = component.borrow();
+			cx.ext.effects.cleanup_effects_internal(&cx);
+		}
+		drop(component);
+		assert_eq!(cancelled.get(), 2);
+		executor.run();
+		assert!(executor.0.borrow().is_empty());
+
+		set_executor(WasmExecutor);
+	}
+}
@@ -17,7 +17,7 @@ pub macro impl_string($d:expr, $ty:ty $(, $a:lifetime )?) {
 			#[cfg(debug_assertions)]
 			tree.name.replace(std::borrow::Cow::Borrowed("[text]"));
 
-			let text = tree.backend.create_text(self.as_ref());
+			let text = tree.create_text(self.as_ref());
 			let prev = tree.set_node(B::text_to_node(text));
 			tree.attach(prev);
 		}
@@ -0,0 +1,109 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use skima::web::event::on;
+use skima::web::html::{div, span};
+use skima::web::root::Root;
+use skima::Markup;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::wasm_bindgen_test;
+use web_sys::HtmlElement;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+fn counter(count: &Rc<Cell<u32>>, stop: bool) -> impl Fn(web_sys::Event) {
+	let count = count.clone();
+	move |event| {
+		count.set(count.get() + 1);
+		if stop {
+			event.stop_propagation();
+		}
+	}
+}
+
+fn component(outer: &Rc<Cell<u32>>, inner: &Rc<Cell<u32>>, stop: bool) -> impl Markup {
+	div((
+		on("click", counter(outer, false)),
+		span((on("click", counter(inner, stop)), "Click")),
+	))
+}
+
+fn click_span(root: &Root<impl Markup>) {
+	root.element()
+		.query_selector("span")
+		.unwrap()
+		.unwrap()
+		.unchecked_into::<HtmlElement>()
+		.click();
+}
+
+#[wasm_bindgen_test]
+pub fn test_delegate() {
+	let document = web_sys::window().unwrap().document().unwrap();
+	let container = document.create_element("div").unwrap();
+
+	let (outer, inner) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+	let root = Root::render_delegated(
+		component(&outer, &inner, false),
+		container.unchecked_into(),
+	);
+
+	click_span(&root);
+	assert_eq!((outer.get(), inner.get()), (1, 1));
+}
+
+#[wasm_bindgen_test]
+pub fn test_delegate_stop_propagation() {
+	let document = web_sys::window().unwrap().document().unwrap();
+	let container = document.create_element("div").unwrap();
+
+	let (outer, inner) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+	let root = Root::render_delegated(
+		component(&outer, &inner, true),
+		container.unchecked_into(),
+	);
+
+	click_span(&root);
+	assert_eq!((outer.get(), inner.get()), (0, 1));
+}
+
+fn two_handlers(outer: &Rc<Cell<u32>>, inner: &Rc<Cell<u32>>, immediate: bool) -> impl Markup {
+	div((
+		on("click", counter(outer, false)),
+		span((
+			on("click", move |event: web_sys::Event| {
+				if immediate {
+					event.stop_immediate_propagation();
+				} else {
+					event.stop_propagation();
+				}
+			}),
+			on("click", counter(inner, false)),
+			"Click",
+		)),
+	))
+}
+
+#[wasm_bindgen_test]
+pub fn test_delegate_stop_immediate_propagation() {
+	let document = web_sys::window().unwrap().document().unwrap();
+
+	// Other handlers of the node still run after `stopPropagation`
+	let (outer, inner) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+	let root = Root::render_delegated(
+		two_handlers(&outer, &inner, false),
+		document.create_element("div").unwrap().unchecked_into(),
+	);
+
+	click_span(&root);
+	assert_eq!((outer.get(), inner.get()), (0, 1));
+
+	let (outer, inner) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+	let root = Root::render_delegated(
+		two_handlers(&outer, &inner, true),
+		document.create_element("div").unwrap().unchecked_into(),
+	);
+
+	click_span(&root);
+	assert_eq!((outer.get(), inner.get()), (0, 0));
+}
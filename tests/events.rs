@@ -0,0 +1,63 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use skima::web::events::on_click;
+use skima::web::html::button;
+use skima::web::root::Root;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::wasm_bindgen_test;
+use web_sys::{HtmlElement, MouseEvent};
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+pub fn test_typed_event() {
+	let document = web_sys::window().unwrap().document().unwrap();
+	let container = document.create_element("div").unwrap();
+
+	let button_seen = Rc::new(Cell::new(0));
+	let root = Root::render(
+		button(on_click({
+			let button_seen = button_seen.clone();
+			move |ev: MouseEvent| button_seen.set(ev.button() + 1)
+		})),
+		container.unchecked_into(),
+	);
+
+	root.element()
+		.first_element_child()
+		.unwrap()
+		.unchecked_into::<HtmlElement>()
+		.click();
+
+	assert_eq!(button_seen.get(), 1);
+}
+
+#[wasm_bindgen_test]
+pub fn test_once() {
+	let document = web_sys::window().unwrap().document().unwrap();
+	let container = document.create_element("div").unwrap();
+
+	let clicks = Rc::new(Cell::new(0));
+	let root = Root::render(
+		button(
+			on_click({
+				let clicks = clicks.clone();
+				move |_: MouseEvent| clicks.set(clicks.get() + 1)
+			})
+			.once(),
+		),
+		container.unchecked_into(),
+	);
+
+	let button = root
+		.element()
+		.first_element_child()
+		.unwrap()
+		.unchecked_into::<HtmlElement>();
+
+	button.click();
+	button.click();
+
+	assert_eq!(clicks.get(), 1);
+}
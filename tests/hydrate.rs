@@ -0,0 +1,87 @@
+use skima::hydrate::HydrationMismatch;
+use skima::web::mock::{MockRoot, Mutation};
+use skima::web::prelude::*;
+
+#[test]
+fn test_hydrate() {
+	let server = MockRoot::render(div((attr("id", "main"), span("ab"))));
+	let dom = server.dom().clone();
+	dom.take_mutations();
+
+	// Adjacent text is merged in the server HTML
+	let mut root = MockRoot::hydrate(
+		dom.clone(),
+		div((attr("id", "main"), span(("a", String::from("b"))))),
+		server.container(),
+	);
+
+	assert!(root.mismatches().is_empty());
+	assert!(dom
+		.mutations()
+		.iter()
+		.all(|m| !matches!(m, Mutation::CreateElement { .. })));
+
+	dom.take_mutations();
+	root.update(div((attr("id", "main"), span(("a", String::from("c"))))));
+
+	assert_eq!(root.inner_html(), r#"<div id="main"><span>ac</span></div>"#);
+	assert!(matches!(
+		dom.mutations().as_slice(),
+		[Mutation::SetText { data, .. }] if data == "c"
+	));
+}
+
+#[test]
+fn test_hydrate_mismatch() {
+	let server = MockRoot::render((div(a("x")), span("extra")));
+	let root = MockRoot::hydrate(server.dom().clone(), div(span("x")), server.container());
+
+	assert_eq!(root.inner_html(), "<div><span>x</span></div>");
+	assert!(matches!(
+		root.mismatches(),
+		[
+			HydrationMismatch::Element {
+				expected: "span",
+				found: Some(_)
+			},
+			HydrationMismatch::Extra { .. }
+		]
+	));
+}
+
+#[test]
+fn test_hydrate_attributes() {
+	let server = MockRoot::render(div((
+		attr("id", "main"),
+		attr("title", "server"),
+		classlist("a"),
+		property("color", "red"),
+		span((attr("hidden", ""), "x")),
+	)));
+
+	let root = MockRoot::hydrate(
+		server.dom().clone(),
+		div((attr("id", "main"), classlist("a"), span("x"))),
+		server.container(),
+	);
+
+	// Server attributes that the markup doesn't set are removed
+	assert_eq!(
+		root.inner_html(),
+		r#"<div class="a" id="main"><span>x</span></div>"#
+	);
+	assert_eq!(
+		root.mismatches(),
+		[
+			HydrationMismatch::Attribute {
+				name: "hidden".to_owned()
+			},
+			HydrationMismatch::Attribute {
+				name: "title".to_owned()
+			},
+			HydrationMismatch::Attribute {
+				name: "style".to_owned()
+			},
+		]
+	);
+}
@@ -0,0 +1,480 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use skima::list::list;
+use skima::web::global::GlobalTarget;
+use skima::web::mock::{MockDom, MockEvent, MockRoot, Mutation};
+use skima::web::prelude::*;
+use skima::Namespace;
+
+#[test]
+fn test_render() {
+	let root = MockRoot::render(div((attr("id", "main"), span("Hello"))));
+	assert_eq!(
+		root.inner_html(),
+		r#"<div id="main"><span>Hello</span></div>"#
+	);
+}
+
+#[test]
+fn test_diff_text() {
+	let mut root = MockRoot::render(div(String::from("one")));
+	root.dom().take_mutations();
+
+	root.update(div(String::from("two")));
+
+	assert_eq!(root.inner_html(), "<div>two</div>");
+	assert!(matches!(
+		root.dom().mutations().as_slice(),
+		[Mutation::SetText { data, .. }] if data == "two"
+	));
+}
+
+fn toggle(show: bool) -> impl Markup<MockDom> {
+	div(("a", show.then(|| span("b"))))
+}
+
+#[test]
+fn test_diff_option() {
+	let mut root = MockRoot::render(toggle(true));
+	assert_eq!(root.inner_html(), "<div>a<span>b</span></div>");
+
+	root.update(toggle(false));
+	assert_eq!(root.inner_html(), "<div>a</div>");
+
+	root.update(toggle(true));
+	assert_eq!(root.inner_html(), "<div>a<span>b</span></div>");
+}
+
+#[test]
+fn test_unmount() {
+	let root = MockRoot::render(div("a"));
+	let dom = root.dom().clone();
+	let container = root.container();
+	dom.take_mutations();
+
+	root.unmount();
+
+	assert_eq!(container.inner_html(), "");
+	assert!(matches!(
+		dom.mutations().as_slice(),
+		[Mutation::Remove { .. }]
+	));
+}
+
+fn items(keys: &[u32]) -> impl Markup<MockDom> {
+	div(list(keys.iter().map(|k| (*k, k.to_string())), |v, _| {
+		span(v.clone())
+	}))
+}
+
+fn span_ids(root: &MockRoot<impl Markup<MockDom>>) -> Vec<(String, usize)> {
+	root.container().children()[0]
+		.children()
+		.iter()
+		.map(|span| (span.inner_html(), span.id()))
+		.collect()
+}
+
+#[test]
+fn test_list_move() {
+	let mut root = MockRoot::render(items(&[1, 2, 3, 4, 5]));
+	let ids = span_ids(&root);
+	root.dom().take_mutations();
+
+	root.update(items(&[1, 4, 3, 2, 5]));
+
+	assert_eq!(
+		root.inner_html(),
+		"<div><span>1</span><span>4</span><span>3</span><span>2</span><span>5</span></div>"
+	);
+
+	// Nodes are moved instead of re-rendered
+	let order = [0, 3, 2, 1, 4];
+	let ids = order.iter().map(|i| ids[*i].clone()).collect::<Vec<_>>();
+	assert_eq!(span_ids(&root), ids);
+
+	let mutations = root.dom().mutations();
+	assert_eq!(mutations.len(), 2);
+	assert!(mutations
+		.iter()
+		.all(|m| matches!(m, Mutation::Move { .. })));
+}
+
+#[test]
+fn test_list_insert_remove() {
+	let mut root = MockRoot::render(items(&[1, 2, 3]));
+	let ids = span_ids(&root);
+
+	root.update(items(&[3, 4, 1]));
+
+	assert_eq!(
+		root.inner_html(),
+		"<div><span>3</span><span>4</span><span>1</span></div>"
+	);
+
+	let next = span_ids(&root);
+	assert_eq!(next[0], ids[2]);
+	assert_eq!(next[2], ids[0]);
+}
+
+#[test]
+fn test_namespace() {
+	let root = MockRoot::render(div(svg::svg(svg::circle(attr("r", "1")))));
+	let svg = root.container().children()[0].children()[0].clone();
+
+	assert_eq!(svg.namespace(), Some(Namespace::Svg));
+	assert_eq!(svg.children()[0].namespace(), Some(Namespace::Svg));
+	assert_eq!(
+		root.inner_html(),
+		r#"<div><svg><circle r="1"></circle></svg></div>"#
+	);
+}
+
+fn input_value(value: &str) -> impl Markup<MockDom> {
+	input(prop("value", value.to_owned()))
+}
+
+#[test]
+fn test_prop() {
+	let mut root = MockRoot::render(input_value("a"));
+	let input = root.container().children()[0].clone();
+	assert_eq!(input.property("value"), Some("a".into()));
+
+	// Same value, nothing to do
+	root.dom().take_mutations();
+	root.update(input_value("a"));
+	assert!(root.dom().mutations().is_empty());
+
+	// The user has typed something, the markup value wins
+	input.set_live_property("value", "ab");
+	root.update(input_value("a"));
+
+	assert_eq!(input.property("value"), Some("a".into()));
+	assert!(matches!(
+		root.dom().mutations().as_slice(),
+		[Mutation::SetDomProperty { value, .. }] if value == "a"
+	));
+}
+
+#[test]
+fn test_global_listener() {
+	let resized = Rc::new(Cell::new(0));
+	let root = MockRoot::render(div((
+		on_window("resize", {
+			let resized = resized.clone();
+			move |_: MockEvent| resized.set(resized.get() + 1)
+		}),
+		// Handlers of DOM events are never called by the mock
+		on_document("visibilitychange", |_: web_sys::Event| unreachable!()),
+		"text",
+	)));
+	assert_eq!(root.inner_html(), "<div>text</div>");
+
+	let dom = root.dom().clone();
+	let window = dom.global(GlobalTarget::Window);
+	dom.dispatch_event(&window, MockEvent::new("resize"));
+	dom.dispatch_event(
+		&dom.global(GlobalTarget::Document),
+		MockEvent::new("visibilitychange"),
+	);
+	assert_eq!(resized.get(), 1);
+
+	root.unmount();
+	dom.dispatch_event(&window, MockEvent::new("resize"));
+	assert_eq!(resized.get(), 1);
+}
+
+#[test]
+fn test_event() {
+	let outer = Rc::new(Cell::new(0));
+	let inner = Rc::new(Cell::new(0));
+
+	let root = MockRoot::render(div((
+		on("click", {
+			let outer = outer.clone();
+			move |_: MockEvent| outer.set(outer.get() + 1)
+		}),
+		span((
+			on("click", {
+				let inner = inner.clone();
+				move |event: MockEvent| {
+					inner.set(inner.get() + 1);
+					if inner.get() > 1 {
+						event.stop_propagation();
+					}
+				}
+			}),
+			"Click",
+		)),
+	)));
+
+	let span = root.container().children()[0].children()[0].clone();
+
+	root.dom().dispatch_event(&span, MockEvent::new("click"));
+	assert_eq!((outer.get(), inner.get()), (1, 1));
+
+	// The second click stops in the span
+	root.dom().dispatch_event(&span, MockEvent::new("click"));
+	assert_eq!((outer.get(), inner.get()), (1, 2));
+
+	let dom = root.dom().clone();
+	dom.take_mutations();
+	root.unmount();
+
+	let removed = dom
+		.mutations()
+		.into_iter()
+		.filter(|m| matches!(m, Mutation::RemoveListener { event: "click", .. }))
+		.count();
+	assert_eq!(removed, 2);
+}
+
+#[test]
+fn test_stylesheet() {
+	let sheet = css!("color: red");
+	let root = MockRoot::render(div((span((sheet, "a")), span((sheet, "b")))));
+	let dom = root.dom().clone();
+
+	let mounts = |dom: &MockDom| {
+		dom.mutations()
+			.iter()
+			.filter(|m| matches!(m, Mutation::MountStyle { .. }))
+			.count()
+	};
+	let unmounts = |dom: &MockDom| {
+		dom.mutations()
+			.iter()
+			.filter(|m| matches!(m, Mutation::UnmountStyle { .. }))
+			.count()
+	};
+
+	assert_eq!(mounts(&dom), 1);
+	assert!(root.inner_html().contains(&format!(r#"<span class="{}">a</span>"#, sheet.class())));
+
+	dom.take_mutations();
+	root.unmount();
+	assert_eq!(unmounts(&dom), 1);
+}
+
+fn todo(done: bool, editing: bool) -> impl Markup<MockDom> {
+	li((
+		classlist("item"),
+		classes([("todo", true), ("completed", done), ("editing", editing)]),
+	))
+}
+
+#[test]
+fn test_classes() {
+	let mut root = MockRoot::render(todo(false, true));
+	assert_eq!(root.inner_html(), r#"<li class="item todo editing"></li>"#);
+	root.dom().take_mutations();
+
+	root.update(todo(true, true));
+	assert!(matches!(
+		root.dom().take_mutations().as_slice(),
+		[Mutation::AddClass { class, .. }] if class == "completed"
+	));
+
+	root.update(todo(true, false));
+	assert!(matches!(
+		root.dom().take_mutations().as_slice(),
+		[Mutation::RemoveClass { class, .. }] if class == "editing"
+	));
+	assert_eq!(
+		root.inner_html(),
+		r#"<li class="item todo completed"></li>"#
+	);
+}
+
+fn shared(editing: bool, extra: &'static str) -> impl Markup<MockDom> {
+	li((
+		classname(String::from(extra)),
+		classes([("item", true), ("editing", editing)]),
+	))
+}
+
+#[test]
+fn test_classes_shared() {
+	let mut root = MockRoot::render(shared(true, "item"));
+	assert_eq!(root.inner_html(), r#"<li class="item editing"></li>"#);
+
+	// `classname` doesn't replace the classes of `classes`
+	root.update(shared(false, "item"));
+	assert_eq!(root.inner_html(), r#"<li class="item"></li>"#);
+
+	// `item` is still added by `classes`
+	root.update(shared(false, "extra"));
+	assert_eq!(root.inner_html(), r#"<li class="item extra"></li>"#);
+}
+
+#[test]
+fn test_attr_rename() {
+	let mut root = MockRoot::render(div(attr(String::from("data-a"), "1")));
+	root.update(div(attr(String::from("data-b"), "1")));
+	assert_eq!(root.inner_html(), r#"<div data-b="1"></div>"#);
+}
+
+fn spread(attributes: &[(&'static str, &'static str)]) -> impl Markup<MockDom> {
+	div((attr("id", "main"), attrs(attributes.iter().copied())))
+}
+
+#[test]
+fn test_attrs() {
+	let mut root = MockRoot::render(spread(&[("data-a", "1"), ("data-b", "2")]));
+	assert_eq!(
+		root.inner_html(),
+		r#"<div data-a="1" data-b="2" id="main"></div>"#
+	);
+	root.dom().take_mutations();
+
+	root.update(spread(&[("data-b", "3"), ("data-c", "4")]));
+	assert_eq!(
+		root.inner_html(),
+		r#"<div data-b="3" data-c="4" id="main"></div>"#
+	);
+	assert_eq!(root.dom().take_mutations().len(), 3);
+}
+
+fn checkbox(checked: bool, index: u32) -> impl Markup<MockDom> {
+	input((
+		input_type(InputType::Checkbox),
+		bool_attr("checked", checked),
+		typed_attr("tabindex", index),
+	))
+}
+
+#[test]
+fn test_typed_attrs() {
+	let mut root = MockRoot::render(checkbox(true, 1));
+	assert_eq!(
+		root.inner_html(),
+		r#"<input checked="" tabindex="1" type="checkbox"></input>"#
+	);
+
+	root.update(checkbox(false, 2));
+	assert_eq!(
+		root.inner_html(),
+		r#"<input tabindex="2" type="checkbox"></input>"#
+	);
+}
+
+fn const_checkbox(index: u32) -> impl Markup<MockDom> {
+	input((
+		input_type(Const(InputType::Checkbox)),
+		bool_attr("disabled", Const(true)),
+		typed_attr("tabindex", Const(index)),
+	))
+}
+
+#[test]
+fn test_const_attrs() {
+	let mut root = MockRoot::render(const_checkbox(1));
+	root.dom().take_mutations();
+
+	// Constant attributes are not diffed
+	root.update(const_checkbox(2));
+	assert!(root.dom().mutations().is_empty());
+	assert_eq!(
+		root.inner_html(),
+		r#"<input disabled="" tabindex="1" type="checkbox"></input>"#
+	);
+}
+
+fn keyed_effects(keys: Vec<u32>, log: Rc<RefCell<Vec<String>>>) -> impl Markup<MockDom> {
+	stateful(move |cx| {
+		for key in keys {
+			let log = log.clone();
+			cx.effect_clean_with_key(&key, (), move |_| {
+				log.borrow_mut().push(format!("run {}", key));
+				move |_| log.borrow_mut().push(format!("clean {}", key))
+			});
+		}
+
+		div(String::from("effects"))
+	})
+}
+
+#[test]
+fn test_effect_key_removed() {
+	let log = Rc::new(RefCell::new(Vec::new()));
+	let mut root = MockRoot::render(keyed_effects(vec![1, 2], log.clone()));
+	assert_eq!(*log.borrow(), ["run 1", "run 2"]);
+
+	root.update(keyed_effects(vec![1], log.clone()));
+	assert_eq!(*log.borrow(), ["run 1", "run 2", "clean 2"]);
+
+	// The removed effect isn't cleaned up again
+	root.update(keyed_effects(vec![1], log.clone()));
+	root.update(keyed_effects(vec![1, 2], log.clone()));
+	assert_eq!(*log.borrow(), ["run 1", "run 2", "clean 2", "run 2"]);
+}
+
+struct Slot(String, Rc<RefCell<Vec<String>>>);
+
+impl Drop for Slot {
+	fn drop(&mut self) {
+		self.1.borrow_mut().push(format!("drop {}", self.0));
+	}
+}
+
+fn keyed_slots(keys: Vec<u32>, log: Rc<RefCell<Vec<String>>>) -> impl Markup<MockDom> {
+	stateful(move |cx| {
+		for key in keys {
+			let slot = |name: &str| {
+				log.borrow_mut().push(format!("new {} {}", name, key));
+				Slot(format!("{} {}", name, key), log.clone())
+			};
+
+			cx.memo_with_key(&key, || Rc::new(slot("memo")));
+			cx.mutable_tagged_with(&key, || slot("mutable"));
+		}
+
+		div(String::from("slots"))
+	})
+}
+
+#[test]
+fn test_memo_key_removed() {
+	let log = Rc::new(RefCell::new(Vec::new()));
+	let mut root = MockRoot::render(keyed_slots(vec![1, 2], log.clone()));
+	root.update(keyed_slots(vec![1, 2], log.clone()));
+	assert_eq!(
+		*log.borrow(),
+		["new memo 1", "new mutable 1", "new memo 2", "new mutable 2"]
+	);
+
+	log.borrow_mut().clear();
+	root.update(keyed_slots(vec![1], log.clone()));
+	log.borrow_mut().sort();
+	assert_eq!(*log.borrow(), ["drop memo 2", "drop mutable 2"]);
+
+	// Slots of a key that is back are created again
+	log.borrow_mut().clear();
+	root.update(keyed_slots(vec![1, 2], log.clone()));
+	assert_eq!(*log.borrow(), ["new memo 2", "new mutable 2"]);
+}
+
+fn deps_effect(dep: u32, log: Rc<RefCell<Vec<String>>>) -> impl Markup<MockDom> {
+	stateful(move |cx| {
+		let log = log.clone();
+		cx.effect_eq_clean(dep, move |_| {
+			log.borrow_mut().push(format!("run {}", dep));
+			move |_| log.borrow_mut().push(format!("clean {}", dep))
+		});
+
+		div(String::from("effects"))
+	})
+}
+
+#[test]
+fn test_effect_deps_changed() {
+	let log = Rc::new(RefCell::new(Vec::new()));
+	let mut root = MockRoot::render(deps_effect(1, log.clone()));
+	root.update(deps_effect(1, log.clone()));
+	assert_eq!(*log.borrow(), ["run 1"]);
+
+	// The previous effect is cleaned up before the next one runs
+	root.update(deps_effect(2, log.clone()));
+	assert_eq!(*log.borrow(), ["run 1", "clean 1", "run 2"]);
+}
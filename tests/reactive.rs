@@ -1,4 +1,8 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use observe::{batch, Var};
+use skima::web::callback::Callback;
 use skima::web::event::on;
 use skima::web::html::div;
 use skima::web::reactive::reactive;
@@ -36,3 +40,75 @@ pub fn test_reactive() {
 	let root = Root::render(component(), container.unchecked_into());
 	std::mem::forget(root);
 }
+
+#[wasm_bindgen_test]
+pub fn test_hooks_call_site() {
+	let document = web_sys::window().unwrap().document().unwrap();
+	let container = document.create_element("div").unwrap();
+
+	let component = reactive(|cx| {
+		// Same types, different call sites
+		let first = cx.mutable(1u32);
+		let second = cx.mutable(2u32);
+
+		// Same call site, explicit keys
+		let memos: Vec<Rc<u32>> = (3..5u32)
+			.map(|i| cx.memo_with_key(&i, || Rc::new(i)))
+			.collect();
+
+		let text = format!(
+			"{} {} {} {}",
+			first.current(),
+			second.current(),
+			memos[0],
+			memos[1]
+		);
+
+		div(text)
+	});
+
+	let root = Root::render(component, container.clone().unchecked_into());
+	assert_eq!(container.inner_html(), "<div>1 2 3 4</div>");
+	std::mem::forget(root);
+}
+
+#[wasm_bindgen_test]
+pub fn test_callback_arity() {
+	let document = web_sys::window().unwrap().document().unwrap();
+	let container = document.create_element("div").unwrap();
+
+	let component = reactive(|cx| {
+		let add: Callback<dyn Fn(u32, u32) -> Option<u32>> =
+			cx.callback(cx.wrap(|_cx, (a, b): (u32, u32)| a + b));
+
+		div(add(1, 2).unwrap().to_string())
+	});
+
+	let root = Root::render(component, container.clone().unchecked_into());
+	assert_eq!(container.inner_html(), "<div>3</div>");
+	std::mem::forget(root);
+}
+
+#[wasm_bindgen_test]
+pub fn test_wrap_after_drop() {
+	let document = web_sys::window().unwrap().document().unwrap();
+	let container = document.create_element("div").unwrap();
+
+	let count = Rc::new(RefCell::new(None));
+	let component = reactive({
+		let count = count.clone();
+		move |cx| {
+			count.replace(Some(
+				Rc::new(cx.wrap(|_cx, ()| 1u32)) as Rc<dyn Fn() -> Option<u32>>
+			));
+			div("count")
+		}
+	});
+
+	let root = Root::render(component, container.clone().unchecked_into());
+	let count = count.borrow_mut().take().unwrap();
+	assert_eq!(count(), Some(1));
+
+	std::mem::drop(root);
+	assert_eq!(count(), None);
+}
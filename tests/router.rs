@@ -0,0 +1,292 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use skima::web::mock::{MockDom, MockRoot};
+use skima::web::prelude::*;
+use skima::web::router::server::route_response;
+use skima::web::router::{
+	not_found, outlet, redirect, render_route, route, route_link, router_view, stream_route,
+	History, MemoryHistory, Routable, Route, RouteResponse, Router, ScrollRestoration,
+	ScrollTarget,
+};
+use skima::web::routing::Navigate;
+use skima::web::ssr::StaticHtml;
+
+fn routes<'a, B: HtmlBackend + 'a>() -> Vec<Route<'a, B>> {
+	vec![
+		route("/", |_| div("home")),
+		redirect("/u/:id", "/users/:id"),
+		route("/users/:id", |params| {
+			div((format!("user {}", params.get("id").unwrap()), outlet()))
+		})
+		.nest([
+			route("/", |_| span("profile")),
+			route("/posts", |_| span("posts")),
+		]),
+		not_found(|_| div("not found")),
+	]
+}
+
+#[test]
+fn test_router() {
+	let history = MemoryHistory::new("/");
+	let router = Router::new(history.clone());
+	let root = MockRoot::render(router_view(router.clone(), routes()));
+	assert_eq!(root.inner_html(), "<div>home</div>");
+
+	router.navigate("/users/1");
+	assert_eq!(root.inner_html(), "<div>user 1<span>profile</span></div>");
+
+	router.navigate("/users/1/posts");
+	assert_eq!(root.inner_html(), "<div>user 1<span>posts</span></div>");
+
+	router.navigate("/u/2");
+	assert_eq!(history.url(), "/users/2");
+	assert_eq!(root.inner_html(), "<div>user 2<span>profile</span></div>");
+
+	router.back();
+	assert_eq!(router.current().path, "/users/1/posts");
+	assert_eq!(root.inner_html(), "<div>user 1<span>posts</span></div>");
+
+	router.navigate("/teams");
+	assert_eq!(root.inner_html(), "<div>not found</div>");
+}
+
+#[test]
+fn test_navigate_action() {
+	let history = MemoryHistory::new("/");
+	let router = Router::new(history.clone());
+	let root = MockRoot::render(router_view(router.clone(), routes()));
+
+	root.tree()
+		.first_child()
+		.dispatch(Box::new(Navigate(String::from("/users/3"))));
+
+	assert_eq!(history.entries(), vec!["/", "/users/3"]);
+	assert_eq!(root.inner_html(), "<div>user 3<span>profile</span></div>");
+}
+
+#[derive(Debug, PartialEq, Routable)]
+enum AppRoute {
+	#[route("/")]
+	Home,
+	#[route("/todos/:id")]
+	Todo { id: u32 },
+	#[route("/search")]
+	Search { q: String, page: Option<u32> },
+	#[route("/files/*path")]
+	File { path: String },
+}
+
+#[test]
+fn test_routable() {
+	let routes = [
+		(AppRoute::Home, "/"),
+		(AppRoute::Todo { id: 42 }, "/todos/42"),
+		(
+			AppRoute::Search {
+				q: String::from("a&b c"),
+				page: Some(2),
+			},
+			"/search?q=a%26b+c&page=2",
+		),
+		(
+			AppRoute::Search {
+				q: String::from("rust"),
+				page: None,
+			},
+			"/search?q=rust",
+		),
+		(
+			AppRoute::File {
+				path: String::from("docs/read me.md"),
+			},
+			"/files/docs/read%20me.md",
+		),
+	];
+
+	for (route, url) in routes {
+		assert_eq!(route.to_url(), url);
+		assert_eq!(AppRoute::from_url(url), Some(route));
+	}
+
+	assert_eq!(AppRoute::from_url("/todos/x"), None);
+	assert_eq!(AppRoute::from_url("/todos/1/edit"), None);
+	assert_eq!(AppRoute::from_url("/search"), None);
+
+	let router = Router::new(MemoryHistory::new("/"));
+	router.navigate_to(&AppRoute::Todo { id: 7 });
+	assert_eq!(router.route(), Some(AppRoute::Todo { id: 7 }));
+
+	let root = MockRoot::render(route_link(&AppRoute::Todo { id: 7 }, "Open"));
+	assert_eq!(root.inner_html(), "<a href=\"/todos/7\">Open</a>");
+}
+
+#[test]
+fn test_server_routes() {
+	assert_eq!(
+		stream_route("/users/1/posts", routes(), section),
+		RouteResponse::Ok(String::from(
+			"<section><div>user 1<span>posts</span></div></section>"
+		))
+	);
+
+	let response = stream_route("/u/2", routes(), section);
+	assert_eq!(response.status(), 302);
+	assert_eq!(response, RouteResponse::Redirect(String::from("/users/2")));
+
+	let response = stream_route("/teams", routes(), section);
+	assert_eq!(response.status(), 404);
+	assert_eq!(
+		response,
+		RouteResponse::NotFound(String::from("<section><div>not found</div></section>"))
+	);
+
+	// Without the catch-all route the router view is empty
+	let response = stream_route("/teams", routes().into_iter().take(3), section);
+	assert_eq!(response.status(), 404);
+	assert_eq!(
+		response,
+		RouteResponse::NotFound(String::from("<section></section>"))
+	);
+
+	let bump = bumpalo::Bump::new();
+	let RouteResponse::Ok(rendered) =
+		render_route(StaticHtml::new(&bump), "/users/1", routes(), section)
+	else {
+		panic!("/users/1 is not rendered");
+	};
+	assert_eq!(
+		rendered.html,
+		"<section><div>user 1<span>profile</span></div></section>"
+	);
+}
+
+#[test]
+fn test_hydrate_route() {
+	let RouteResponse::Ok(server) = route_response("/users/1", routes(), section, MockRoot::render)
+	else {
+		panic!("/users/1 is not rendered");
+	};
+
+	let history = MemoryHistory::new("/users/1");
+	let router = Router::new(history);
+	let client = MockRoot::hydrate(
+		server.dom().clone(),
+		section(router_view(router.clone(), routes())),
+		server.container(),
+	);
+
+	assert!(client.mismatches().is_empty());
+
+	router.navigate("/users/1/posts");
+	assert_eq!(
+		client.inner_html(),
+		"<section><div>user 1<span>posts</span></div></section>"
+	);
+}
+
+#[test]
+fn test_hydrate_not_found() {
+	let bump = bumpalo::Bump::new();
+	let RouteResponse::NotFound(html) =
+		render_route(StaticHtml::new(&bump), "/teams", routes(), section)
+	else {
+		panic!("/teams is found");
+	};
+
+	// The same page in a MockDom, to hydrate it
+	let RouteResponse::NotFound(server) =
+		route_response("/teams", routes(), section, MockRoot::render)
+	else {
+		panic!("/teams is found");
+	};
+	assert_eq!(server.inner_html(), html.html);
+
+	let router = Router::new(MemoryHistory::new("/teams"));
+	let client = MockRoot::hydrate(
+		server.dom().clone(),
+		section(router_view(router.clone(), routes())),
+		server.container(),
+	);
+
+	assert!(client.mismatches().is_empty());
+
+	router.navigate("/");
+	assert_eq!(client.inner_html(), "<section><div>home</div></section>");
+}
+
+#[derive(Default)]
+struct MockScrollState {
+	offset: (f64, f64),
+	ids: Vec<&'static str>,
+	/// Element scrolled into view
+	scrolled_to: Option<String>,
+	focused: Option<String>,
+}
+
+#[derive(Clone, Default)]
+struct MockScroll(Rc<RefCell<MockScrollState>>);
+
+impl ScrollTarget for MockScroll {
+	fn offset(&self) -> (f64, f64) {
+		self.0.borrow().offset
+	}
+
+	fn scroll_to(&self, x: f64, y: f64) {
+		let mut state = self.0.borrow_mut();
+		state.offset = (x, y);
+		state.scrolled_to = None;
+	}
+
+	fn scroll_to_id(&self, id: &str) -> bool {
+		let mut state = self.0.borrow_mut();
+		if !state.ids.contains(&id) {
+			return false;
+		}
+
+		state.scrolled_to = Some(id.to_owned());
+		true
+	}
+
+	fn focus(&self, selector: &str) -> bool {
+		self.0.borrow_mut().focused = Some(selector.to_owned());
+		true
+	}
+}
+
+#[test]
+fn test_scroll_restoration() {
+	let router = Router::new(MemoryHistory::new("/"));
+	let _root = MockRoot::render(router_view(router.clone(), routes()));
+
+	let scroll = MockScroll::default();
+	scroll.0.borrow_mut().ids.push("comments");
+	router.set_scroll(ScrollRestoration::new(scroll.clone()).focus("main"));
+
+	scroll.0.borrow_mut().offset = (0.0, 300.0);
+	router.navigate("/users/1");
+	assert_eq!(scroll.offset(), (0.0, 0.0));
+	assert_eq!(scroll.0.borrow().focused.as_deref(), Some("main"));
+
+	scroll.0.borrow_mut().offset = (0.0, 50.0);
+	router.navigate("/users/1/posts#comments");
+	assert_eq!(scroll.0.borrow().scrolled_to.as_deref(), Some("comments"));
+
+	router.back();
+	assert_eq!(scroll.offset(), (0.0, 50.0));
+
+	router.back();
+	assert_eq!(scroll.offset(), (0.0, 300.0));
+
+	// Missing fragment targets scroll to the top
+	router.navigate("/users/2#missing");
+	assert_eq!(scroll.offset(), (0.0, 0.0));
+	assert_eq!(scroll.0.borrow().scrolled_to, None);
+	// Only the location of the redirect is entered
+	scroll.0.borrow_mut().offset = (0.0, 100.0);
+	router.navigate("/u/3#comments");
+	assert_eq!(router.current().path, "/users/3");
+	assert_eq!(scroll.offset(), (0.0, 0.0));
+	assert_eq!(scroll.0.borrow().scrolled_to, None);
+}